@@ -1,161 +1,390 @@
-use color_eyre::eyre::{Context, Result};
-use reqwest::Client;
+use chrono::Local;
+use reqwest::{header::RETRY_AFTER, Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde_json::json;
 
+use crate::comments::{self, CommentTarget};
+use crate::error::ApiError;
+use crate::filters;
+use crate::labels;
 use crate::projects;
 use crate::sections;
+use crate::sync::{self, SyncDelta, SyncResponse};
 use crate::tasks;
 use crate::tasks::Task;
-use crate::TaskResult;
 
-pub async fn fetch_projects(client: &Client) -> Result<Vec<projects::Project>> {
-    let response = client
-        .get("https://api.todoist.com/rest/v2/projects")
-        .send()
-        .await
-        .context("Failed to send request to fetch projects")?;
+/// How much of an unexpected response body ends up in an error message.
+const SNIPPET_LENGTH: usize = 200;
 
-    if response.status().is_client_error() {
-        return Err(color_eyre::eyre::eyre!("Received a 400 error: {:?}. This would most likely be because of an incorrect token. Check your config file for token.", response.status()));
+/// Sends the request and returns the body of a successful response.
+async fn send(request: RequestBuilder) -> Result<String, ApiError> {
+    let response = request.send().await?;
+    let status = response.status();
+
+    if status.is_success() {
+        return Ok(response.text().await?);
+    }
+
+    let code = status.as_u16();
+    match code {
+        401 | 403 => Err(ApiError::Unauthorized(code)),
+        429 => {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok());
+            Err(ApiError::RateLimited { retry_after })
+        }
+        _ => {
+            // could be a whole HTML page from a proxy
+            let body = snippet(&response.text().await.unwrap_or_default());
+            if status.is_server_error() {
+                Err(ApiError::Server { status: code, body })
+            } else {
+                Err(ApiError::Http { status: code, body })
+            }
+        }
     }
+}
 
-    let response_text = response
-        .text()
-        .await
-        .context("Failed to read response text")?;
+fn decode<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::Decode {
+        message: e.to_string(),
+        snippet: snippet(body),
+    })
+}
 
-    let serialized: Vec<projects::Project> = serde_json::from_str(&response_text)
-        .context("Failed to deserialize response into Vec<Project>")?;
-    Ok(serialized)
+fn snippet(body: &str) -> String {
+    body.chars().take(SNIPPET_LENGTH).collect()
 }
 
-pub async fn fetch_tasks(
-    client: &reqwest::Client,
-) -> Result<Vec<tasks::Task>, Box<dyn std::error::Error>> {
-    let response = client
-        .get("https://api.todoist.com/rest/v2/tasks")
-        .send()
-        .await
-        .unwrap()
-        .text()
-        .await
-        .unwrap();
+pub async fn fetch_projects(
+    client: &Client,
+    base_url: &str,
+) -> Result<Vec<projects::Project>, ApiError> {
+    let response = send(client.get(format!("{}/rest/v2/projects", base_url))).await?;
+    decode(&response)
+}
 
-    let serialized: Vec<tasks::Task> = serde_json::from_str(&response).unwrap();
-    Ok(serialized)
+/// The people a shared project's tasks can be assigned to.
+pub async fn fetch_collaborators(
+    client: &Client,
+    base_url: &str,
+    project_id: &str,
+) -> Result<Vec<projects::Collaborator>, ApiError> {
+    let response = send(client.get(format!(
+        "{}/rest/v2/projects/{}/collaborators",
+        base_url, project_id
+    )))
+    .await?;
+    decode(&response)
+}
+
+pub async fn fetch_tasks(client: &Client, base_url: &str) -> Result<Vec<tasks::Task>, ApiError> {
+    let response = send(client.get(format!("{}/rest/v2/tasks", base_url))).await?;
+    decode(&response)
 }
 
 pub async fn fetch_sections(
-    client: &reqwest::Client,
-) -> Result<Vec<sections::Section>, Box<dyn std::error::Error>> {
-    let response = client
-        .get("https://api.todoist.com/rest/v2/sections")
-        .send()
-        .await
-        .unwrap()
-        .text()
-        .await
-        .unwrap();
+    client: &Client,
+    base_url: &str,
+) -> Result<Vec<sections::Section>, ApiError> {
+    let response = send(client.get(format!("{}/rest/v2/sections", base_url))).await?;
+    decode(&response)
+}
 
-    let serialized: Vec<sections::Section> = serde_json::from_str(&response).unwrap();
-    Ok(serialized)
+pub async fn fetch_labels(client: &Client, base_url: &str) -> Result<Vec<labels::Label>, ApiError> {
+    let response = send(client.get(format!("{}/rest/v2/labels", base_url))).await?;
+    decode(&response)
 }
 
 pub async fn update_task(
-    client: &reqwest::Client,
+    client: &Client,
+    base_url: &str,
     json: serde_json::Value,
     task_id: String,
-    tx: std::sync::mpsc::Sender<TaskResult>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let response = match client
-        .post(format!("https://api.todoist.com/rest/v2/tasks/{}", task_id))
-        .json(&json)
-        .send()
-        .await
-    {
-        Ok(resp) => resp,
-        Err(e) => {
-            let error_msg = format!("Network error: {}", e);
-            tx.send(TaskResult::Error(error_msg)).unwrap();
-            return Err(e.into());
-        }
-    };
+) -> Result<Task, ApiError> {
+    let response = send(
+        client
+            .post(format!("{}/rest/v2/tasks/{}", base_url, task_id))
+            .json(&json),
+    )
+    .await?;
+    decode(&response)
+}
 
-    let response_ref = &response;
+pub async fn close_task(client: &Client, base_url: &str, task_id: String) -> Result<(), ApiError> {
+    let url = format!("{}/rest/v2/tasks/{}/close", base_url, task_id);
+    send(client.post(url)).await?;
+    Ok(())
+}
 
-    match response_ref.error_for_status_ref() {
-        Ok(resp) => resp,
-        Err(e) => {
-            let status_code = e.status().unwrap_or_default();
-            let response_text = response.text().await.unwrap_or_default();
-            let error_msg = format!("API error: {} ({}) \n\n {}", status_code, e, response_text);
-            tx.send(TaskResult::Error(error_msg)).unwrap();
-            return Err(e.into());
-        }
-    };
+pub async fn delete_task(client: &Client, base_url: &str, task_id: String) -> Result<(), ApiError> {
+    let url = format!("{}/rest/v2/tasks/{}", base_url, task_id);
+    send(client.delete(url)).await?;
+    Ok(())
+}
 
-    let response_text = match response.text().await {
-        Ok(text) => text,
-        Err(e) => {
-            let error_msg = format!("Failed to read response: {}", e);
-            tx.send(TaskResult::Error(error_msg)).unwrap();
-            return Err(e.into());
-        }
-    };
+pub async fn create_task(
+    client: &Client,
+    base_url: &str,
+    json: serde_json::Value,
+) -> Result<Task, ApiError> {
+    let response = send(
+        client
+            .post(format!("{}/rest/v2/tasks", base_url))
+            .json(&json),
+    )
+    .await?;
+    decode(&response)
+}
 
-    match serde_json::from_str::<Task>(&response_text) {
-        Ok(serialized) => {
-            tx.send(TaskResult::Task(serialized)).unwrap();
-            Ok(())
-        }
-        Err(e) => {
-            let error_msg = format!(
-                "Failed to parse response: {} (Response was: {})",
-                e, response_text
-            );
-            tx.send(TaskResult::Error(error_msg)).unwrap();
-            Err(e.into())
-        }
-    }
+pub async fn fetch_comments(
+    client: &Client,
+    base_url: &str,
+    target: &CommentTarget,
+) -> Result<Vec<comments::Comment>, ApiError> {
+    let response = send(
+        client
+            .get(format!("{}/rest/v2/comments", base_url))
+            .query(&[target.param()]),
+    )
+    .await?;
+    decode(&response)
 }
 
-pub async fn close_task(
-    client: &reqwest::Client,
-    task_id: String,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let url = format!("https://api.todoist.com/rest/v2/tasks/{}/close", task_id);
+pub async fn create_comment(
+    client: &Client,
+    base_url: &str,
+    json: serde_json::Value,
+) -> Result<comments::Comment, ApiError> {
+    let response = send(
+        client
+            .post(format!("{}/rest/v2/comments", base_url))
+            .json(&json),
+    )
+    .await?;
+    decode(&response)
+}
 
-    let _response = client.post(url).send().await?;
+pub async fn update_comment(
+    client: &Client,
+    base_url: &str,
+    json: serde_json::Value,
+    comment_id: String,
+) -> Result<comments::Comment, ApiError> {
+    let response = send(
+        client
+            .post(format!("{}/rest/v2/comments/{}", base_url, comment_id))
+            .json(&json),
+    )
+    .await?;
+    decode(&response)
+}
 
+pub async fn delete_comment(
+    client: &Client,
+    base_url: &str,
+    comment_id: String,
+) -> Result<(), ApiError> {
+    let url = format!("{}/rest/v2/comments/{}", base_url, comment_id);
+    send(client.delete(url)).await?;
     Ok(())
 }
 
-pub async fn delete_task(
-    client: &reqwest::Client,
-    task_id: String,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let url = format!("https://api.todoist.com/rest/v2/tasks/{}", task_id);
+pub async fn sync(
+    client: &Client,
+    base_url: &str,
+    sync_token: &str,
+) -> Result<SyncDelta, ApiError> {
+    let response = send(client.post(format!("{}/sync/v9/sync", base_url)).form(&[
+        ("sync_token", sync_token),
+        ("resource_types", sync::RESOURCE_TYPES),
+    ]))
+    .await?;
+    decode::<SyncResponse>(&response).map(SyncDelta::from)
+}
 
-    let _response = client.delete(url).send().await?;
+/// Filters have no REST endpoint, they come from a full sync of just them.
+pub async fn fetch_filters(
+    client: &Client,
+    base_url: &str,
+) -> Result<Vec<filters::SavedFilter>, ApiError> {
+    let response = send(client.post(format!("{}/sync/v9/sync", base_url)).form(&[
+        ("sync_token", sync::FULL_SYNC_TOKEN),
+        ("resource_types", sync::FILTER_RESOURCE_TYPES),
+    ]))
+    .await?;
+    decode::<SyncResponse>(&response).map(|response| SyncDelta::from(response).filters.updated)
+}
+
+pub async fn create_project(
+    client: &Client,
+    base_url: &str,
+    json: serde_json::Value,
+) -> Result<projects::Project, ApiError> {
+    let response = send(
+        client
+            .post(format!("{}/rest/v2/projects", base_url))
+            .json(&json),
+    )
+    .await?;
+    decode(&response)
+}
 
+pub async fn update_project(
+    client: &Client,
+    base_url: &str,
+    json: serde_json::Value,
+    project_id: String,
+) -> Result<projects::Project, ApiError> {
+    let response = send(
+        client
+            .post(format!("{}/rest/v2/projects/{}", base_url, project_id))
+            .json(&json),
+    )
+    .await?;
+    decode(&response)
+}
+
+pub async fn delete_project(
+    client: &Client,
+    base_url: &str,
+    project_id: String,
+) -> Result<(), ApiError> {
+    let url = format!("{}/rest/v2/projects/{}", base_url, project_id);
+    send(client.delete(url)).await?;
     Ok(())
 }
 
-pub async fn create_task<'a>(
-    client: &reqwest::Client,
+pub async fn create_section(
+    client: &Client,
+    base_url: &str,
     json: serde_json::Value,
-    tx: std::sync::mpsc::Sender<TaskResult>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let response = client
-        .post("https://api.todoist.com/rest/v2/tasks")
-        .json(&json)
-        .send()
-        .await
-        .unwrap()
-        .text()
-        .await
-        .unwrap();
-
-    let serialized: Task = serde_json::from_str(&response).unwrap();
-    tx.send(TaskResult::Task(serialized)).unwrap();
+) -> Result<sections::Section, ApiError> {
+    let response = send(
+        client
+            .post(format!("{}/rest/v2/sections", base_url))
+            .json(&json),
+    )
+    .await?;
+    decode(&response)
+}
+
+pub async fn update_section(
+    client: &Client,
+    base_url: &str,
+    json: serde_json::Value,
+    section_id: String,
+) -> Result<sections::Section, ApiError> {
+    let response = send(
+        client
+            .post(format!("{}/rest/v2/sections/{}", base_url, section_id))
+            .json(&json),
+    )
+    .await?;
+    decode(&response)
+}
+
+pub async fn delete_section(
+    client: &Client,
+    base_url: &str,
+    section_id: String,
+) -> Result<(), ApiError> {
+    let url = format!("{}/rest/v2/sections/{}", base_url, section_id);
+    send(client.delete(url)).await?;
     Ok(())
 }
+
+/// Runs a single Sync API command, for the changes REST can't make.
+async fn command(
+    client: &Client,
+    base_url: &str,
+    command_type: &str,
+    args: serde_json::Value,
+) -> Result<(), ApiError> {
+    let uuid = format!(
+        "{}-{}",
+        command_type,
+        Local::now().timestamp_nanos_opt().unwrap_or(0)
+    );
+    let commands = json!([{ "type": command_type, "uuid": uuid, "args": args }]).to_string();
+    let response = send(
+        client
+            .post(format!("{}/sync/v9/sync", base_url))
+            .form(&[("commands", commands)]),
+    )
+    .await?;
+
+    let response: serde_json::Value = decode(&response)?;
+    let status = &response["sync_status"][&uuid];
+    if status == "ok" {
+        return Ok(());
+    }
+    Err(ApiError::Http {
+        status: status["http_code"].as_u64().unwrap_or(400) as u16,
+        body: status["error"]
+            .as_str()
+            .map_or_else(|| status.to_string(), |error| error.to_string()),
+    })
+}
+
+/// Moves a task to `section_id`, or out of any section of `project_id`.
+pub async fn move_task(
+    client: &Client,
+    base_url: &str,
+    task_id: String,
+    project_id: String,
+    section_id: Option<String>,
+) -> Result<(), ApiError> {
+    let args = match section_id {
+        Some(section_id) => json!({ "id": task_id, "section_id": section_id }),
+        None => json!({ "id": task_id, "project_id": project_id }),
+    };
+    command(client, base_url, "item_move", args).await
+}
+
+/// REST can't change a project's parent, Sync can.
+pub async fn move_project(
+    client: &Client,
+    base_url: &str,
+    project_id: String,
+    parent_id: Option<String>,
+) -> Result<(), ApiError> {
+    let args = json!({ "id": project_id, "parent_id": parent_id });
+    command(client, base_url, "project_move", args).await
+}
+
+pub async fn archive_project(
+    client: &Client,
+    base_url: &str,
+    project_id: String,
+) -> Result<(), ApiError> {
+    command(
+        client,
+        base_url,
+        "project_archive",
+        json!({ "id": project_id }),
+    )
+    .await
+}
+
+/// Sets the order of a project's sections, given as `(id, order)`.
+pub async fn reorder_sections(
+    client: &Client,
+    base_url: &str,
+    sections: Vec<(String, i32)>,
+) -> Result<(), ApiError> {
+    let sections: Vec<_> = sections
+        .into_iter()
+        .map(|(id, order)| json!({ "id": id, "section_order": order }))
+        .collect();
+    command(
+        client,
+        base_url,
+        "section_reorder",
+        json!({ "sections": sections }),
+    )
+    .await
+}
@@ -0,0 +1,140 @@
+use std::future::Future;
+
+use crate::{
+    comments::{Comment, CommentTarget},
+    error::ApiError,
+    filters::SavedFilter,
+    labels::Label,
+    projects::{Collaborator, Project},
+    sections::Section,
+    sync::SyncDelta,
+    tasks::Task,
+};
+
+mod memory;
+mod rest;
+
+pub use memory::MemoryBackend;
+pub use rest::RestBackend;
+
+/// Every remote operation the app performs against Todoist.
+///
+/// `RestBackend` talks to the REST API, `MemoryBackend` keeps everything in
+/// process so the TUI can run without a network connection or an account.
+pub trait TodoistBackend: Clone + Send + Sync + 'static {
+    fn fetch_projects(&self) -> impl Future<Output = Result<Vec<Project>, ApiError>> + Send;
+
+    /// The people in a shared project.
+    fn fetch_collaborators(
+        &self,
+        project_id: String,
+    ) -> impl Future<Output = Result<Vec<Collaborator>, ApiError>> + Send;
+
+    fn fetch_tasks(&self) -> impl Future<Output = Result<Vec<Task>, ApiError>> + Send;
+
+    fn fetch_sections(&self) -> impl Future<Output = Result<Vec<Section>, ApiError>> + Send;
+
+    fn fetch_labels(&self) -> impl Future<Output = Result<Vec<Label>, ApiError>> + Send;
+
+    /// The filters saved in the account.
+    fn fetch_filters(&self) -> impl Future<Output = Result<Vec<SavedFilter>, ApiError>> + Send;
+
+    fn create_task(
+        &self,
+        json: serde_json::Value,
+    ) -> impl Future<Output = Result<Task, ApiError>> + Send;
+
+    fn update_task(
+        &self,
+        task_id: String,
+        json: serde_json::Value,
+    ) -> impl Future<Output = Result<Task, ApiError>> + Send;
+
+    /// Puts a task in `section_id`, or in `project_id` outside any section.
+    fn move_task(
+        &self,
+        task_id: String,
+        project_id: String,
+        section_id: Option<String>,
+    ) -> impl Future<Output = Result<(), ApiError>> + Send;
+
+    fn close_task(&self, task_id: String) -> impl Future<Output = Result<(), ApiError>> + Send;
+
+    fn delete_task(&self, task_id: String) -> impl Future<Output = Result<(), ApiError>> + Send;
+
+    fn create_project(
+        &self,
+        json: serde_json::Value,
+    ) -> impl Future<Output = Result<Project, ApiError>> + Send;
+
+    fn update_project(
+        &self,
+        project_id: String,
+        json: serde_json::Value,
+    ) -> impl Future<Output = Result<Project, ApiError>> + Send;
+
+    /// Nests a project under `parent_id`, or moves it to the top level.
+    fn move_project(
+        &self,
+        project_id: String,
+        parent_id: Option<String>,
+    ) -> impl Future<Output = Result<(), ApiError>> + Send;
+
+    fn archive_project(
+        &self,
+        project_id: String,
+    ) -> impl Future<Output = Result<(), ApiError>> + Send;
+
+    fn delete_project(
+        &self,
+        project_id: String,
+    ) -> impl Future<Output = Result<(), ApiError>> + Send;
+
+    fn create_section(
+        &self,
+        json: serde_json::Value,
+    ) -> impl Future<Output = Result<Section, ApiError>> + Send;
+
+    fn update_section(
+        &self,
+        section_id: String,
+        json: serde_json::Value,
+    ) -> impl Future<Output = Result<Section, ApiError>> + Send;
+
+    /// Sets the order of a project's sections, given as `(id, order)`.
+    fn reorder_sections(
+        &self,
+        sections: Vec<(String, i32)>,
+    ) -> impl Future<Output = Result<(), ApiError>> + Send;
+
+    /// Deletes the section along with its tasks.
+    fn delete_section(
+        &self,
+        section_id: String,
+    ) -> impl Future<Output = Result<(), ApiError>> + Send;
+
+    /// The comments of a task or project.
+    fn fetch_comments(
+        &self,
+        target: CommentTarget,
+    ) -> impl Future<Output = Result<Vec<Comment>, ApiError>> + Send;
+
+    fn create_comment(
+        &self,
+        json: serde_json::Value,
+    ) -> impl Future<Output = Result<Comment, ApiError>> + Send;
+
+    fn update_comment(
+        &self,
+        comment_id: String,
+        json: serde_json::Value,
+    ) -> impl Future<Output = Result<Comment, ApiError>> + Send;
+
+    fn delete_comment(
+        &self,
+        comment_id: String,
+    ) -> impl Future<Output = Result<(), ApiError>> + Send;
+
+    /// Changes since `sync_token`, or everything when it is `sync::FULL_SYNC_TOKEN`.
+    fn sync(&self, sync_token: String) -> impl Future<Output = Result<SyncDelta, ApiError>> + Send;
+}
@@ -0,0 +1,471 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    comments::{Comment, CommentTarget},
+    error::ApiError,
+    filters::SavedFilter,
+    labels::Label,
+    projects::{remove_project, Collaborator, ListType, Project},
+    sections::Section,
+    sync::{Changes, SyncDelta},
+    tasks::{self, Due, Task},
+};
+
+use super::TodoistBackend;
+
+/// A backend that never leaves the process. Handy for demos and for running
+/// the TUI without an account.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+    store: Arc<Mutex<Store>>,
+}
+
+#[derive(Debug, Default)]
+struct Store {
+    projects: Vec<Project>,
+    tasks: Vec<Task>,
+    sections: Vec<Section>,
+    labels: Vec<Label>,
+    filters: Vec<SavedFilter>,
+    comments: Vec<Comment>,
+    next_id: u64,
+}
+
+impl Store {
+    fn next_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("mem-{}", self.next_id)
+    }
+
+    /// Keeps the comment counts of tasks and projects in step.
+    fn count_comment(&mut self, comment: &Comment, added: bool) {
+        match comment.target() {
+            Some(CommentTarget::Task(id)) => {
+                if let Some(task) = self.tasks.iter_mut().find(|task| task.id == id) {
+                    task.comment_count = if added {
+                        task.comment_count.saturating_add(1)
+                    } else {
+                        task.comment_count.saturating_sub(1)
+                    };
+                }
+            }
+            Some(CommentTarget::Project(id)) => {
+                if let Some(project) = self.projects.iter_mut().find(|project| project.id == id) {
+                    project.comment_count = if added {
+                        project.comment_count.saturating_add(1)
+                    } else {
+                        project.comment_count.saturating_sub(1)
+                    };
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Like Todoist, creates the labels a task uses that don't exist yet.
+    fn add_labels(&mut self, names: &[String]) {
+        for name in names {
+            if !self.labels.iter().any(|label| label.name == *name) {
+                let label = Label {
+                    id: self.next_id(),
+                    name: name.clone(),
+                    color: "charcoal".to_string(),
+                    order: self.labels.len() as i32 + 1,
+                    is_favorite: false,
+                };
+                self.labels.push(label);
+            }
+        }
+    }
+}
+
+impl MemoryBackend {
+    pub fn new(projects: Vec<Project>, tasks: Vec<Task>, sections: Vec<Section>) -> MemoryBackend {
+        MemoryBackend {
+            store: Arc::new(Mutex::new(Store {
+                projects,
+                tasks,
+                sections,
+                labels: Vec::new(),
+                filters: Vec::new(),
+                comments: Vec::new(),
+                next_id: 0,
+            })),
+        }
+    }
+
+    /// A backend seeded with an inbox and a couple of tasks.
+    pub fn demo() -> MemoryBackend {
+        let backend = MemoryBackend::new(
+            vec![Project {
+                id: "inbox".to_string(),
+                name: "Inbox".to_string(),
+                comment_count: 0,
+                order: 0,
+                color: "grey".to_string(),
+                is_shared: false,
+                is_favorite: false,
+                is_inbox_project: true,
+                is_team_inbox: false,
+                view_style: ListType::List,
+                url: String::new(),
+                parent_id: None,
+            }],
+            Vec::new(),
+            Vec::new(),
+        );
+
+        {
+            let mut store = backend.store.lock().unwrap();
+            for (content, due_string) in [
+                ("Try out the todoist TUI", "today"),
+                ("Point base_url at a real server", "tomorrow"),
+            ] {
+                let json = serde_json::json!({
+                    "content": content,
+                    "project_id": "inbox",
+                    "due_string": due_string,
+                });
+                let task = new_task(&mut store, &json);
+                store.tasks.push(task);
+            }
+        }
+
+        backend
+    }
+}
+
+impl TodoistBackend for MemoryBackend {
+    async fn fetch_projects(&self) -> Result<Vec<Project>, ApiError> {
+        Ok(self.store.lock().unwrap().projects.clone())
+    }
+
+    /// Projects never get shared here, so there is no one in them.
+    async fn fetch_collaborators(&self, project_id: String) -> Result<Vec<Collaborator>, ApiError> {
+        let store = self.store.lock().unwrap();
+        if !store
+            .projects
+            .iter()
+            .any(|project| project.id == project_id)
+        {
+            return Err(not_found("Project", &project_id));
+        }
+        Ok(Vec::new())
+    }
+
+    async fn fetch_tasks(&self) -> Result<Vec<Task>, ApiError> {
+        Ok(self.store.lock().unwrap().tasks.clone())
+    }
+
+    async fn fetch_sections(&self) -> Result<Vec<Section>, ApiError> {
+        Ok(self.store.lock().unwrap().sections.clone())
+    }
+
+    async fn fetch_labels(&self) -> Result<Vec<Label>, ApiError> {
+        Ok(self.store.lock().unwrap().labels.clone())
+    }
+
+    async fn fetch_filters(&self) -> Result<Vec<SavedFilter>, ApiError> {
+        Ok(self.store.lock().unwrap().filters.clone())
+    }
+
+    async fn create_task(&self, json: serde_json::Value) -> Result<Task, ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let task = new_task(&mut store, &json);
+        store.add_labels(&task.labels);
+        store.tasks.push(task.clone());
+        Ok(task)
+    }
+
+    async fn update_task(
+        &self,
+        task_id: String,
+        json: serde_json::Value,
+    ) -> Result<Task, ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let task = store
+            .tasks
+            .iter_mut()
+            .find(|task| task.id == task_id)
+            .ok_or_else(|| not_found("Task", &task_id))?;
+
+        if let Some(content) = json["content"].as_str() {
+            task.content = content.to_string();
+        }
+        if let Some(description) = json["description"].as_str() {
+            task.description = description.to_string();
+        }
+        if let Some(priority) = json["priority"].as_u64() {
+            task.priority = priority as u8;
+        }
+        if json["due_string"].is_string()
+            || json["due_date"].is_string()
+            || json["due_datetime"].is_string()
+        {
+            task.due = Due::from_json(&json);
+        }
+        if let Ok(labels) = serde_json::from_value(json["labels"].clone()) {
+            task.labels = labels;
+        }
+
+        let task = task.clone();
+        store.add_labels(&task.labels);
+        Ok(task)
+    }
+
+    async fn move_task(
+        &self,
+        task_id: String,
+        project_id: String,
+        section_id: Option<String>,
+    ) -> Result<(), ApiError> {
+        let mut store = self.store.lock().unwrap();
+        for task in store.tasks.iter_mut() {
+            // subtasks follow their parent
+            if task.id == task_id || task.parent_id.as_ref() == Some(&task_id) {
+                task.project_id = project_id.clone();
+                task.section_id = section_id.clone();
+            }
+        }
+        if !store.tasks.iter().any(|task| task.id == task_id) {
+            return Err(not_found("Task", &task_id));
+        }
+        Ok(())
+    }
+
+    async fn close_task(&self, task_id: String) -> Result<(), ApiError> {
+        self.delete_task(task_id).await
+    }
+
+    async fn delete_task(&self, task_id: String) -> Result<(), ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let len = store.tasks.len();
+        tasks::remove_task(&mut store.tasks, &task_id);
+        if store.tasks.len() == len {
+            return Err(not_found("Task", &task_id));
+        }
+        Ok(())
+    }
+
+    async fn create_project(&self, json: serde_json::Value) -> Result<Project, ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let id = store.next_id();
+        let mut project = Project::from_json(id, &json);
+        project.order = store.projects.len() as i32 + 1;
+        store.projects.push(project.clone());
+        Ok(project)
+    }
+
+    async fn update_project(
+        &self,
+        project_id: String,
+        json: serde_json::Value,
+    ) -> Result<Project, ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let project = store
+            .projects
+            .iter_mut()
+            .find(|project| project.id == project_id)
+            .ok_or_else(|| not_found("Project", &project_id))?;
+
+        if let Some(name) = json["name"].as_str() {
+            project.name = name.to_string();
+        }
+        if let Some(color) = json["color"].as_str() {
+            project.color = color.to_string();
+        }
+        if let Some(is_favorite) = json["is_favorite"].as_bool() {
+            project.is_favorite = is_favorite;
+        }
+        if let Ok(view_style) = serde_json::from_value(json["view_style"].clone()) {
+            project.view_style = view_style;
+        }
+
+        Ok(project.clone())
+    }
+
+    async fn move_project(
+        &self,
+        project_id: String,
+        parent_id: Option<String>,
+    ) -> Result<(), ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let project = store
+            .projects
+            .iter_mut()
+            .find(|project| project.id == project_id)
+            .ok_or_else(|| not_found("Project", &project_id))?;
+        project.parent_id = parent_id;
+        Ok(())
+    }
+
+    async fn archive_project(&self, project_id: String) -> Result<(), ApiError> {
+        self.delete_project(project_id).await
+    }
+
+    async fn delete_project(&self, project_id: String) -> Result<(), ApiError> {
+        let mut store = self.store.lock().unwrap();
+        if !store
+            .projects
+            .iter()
+            .any(|project| project.id == project_id)
+        {
+            return Err(not_found("Project", &project_id));
+        }
+        let Store {
+            projects,
+            tasks,
+            sections,
+            ..
+        } = &mut *store;
+        remove_project(projects, tasks, &project_id);
+        sections.retain(|section| projects.iter().any(|p| p.id == section.project_id));
+        Ok(())
+    }
+
+    async fn create_section(&self, json: serde_json::Value) -> Result<Section, ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let id = store.next_id();
+        let mut section = Section::from_json(id, &json);
+        if json["order"].is_null() {
+            section.order = store.sections.len() as i32 + 1;
+        }
+        store.sections.push(section.clone());
+        Ok(section)
+    }
+
+    async fn update_section(
+        &self,
+        section_id: String,
+        json: serde_json::Value,
+    ) -> Result<Section, ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let section = store
+            .sections
+            .iter_mut()
+            .find(|section| section.id == section_id)
+            .ok_or_else(|| not_found("Section", &section_id))?;
+        if let Some(name) = json["name"].as_str() {
+            section.name = name.to_string();
+        }
+        Ok(section.clone())
+    }
+
+    async fn reorder_sections(&self, sections: Vec<(String, i32)>) -> Result<(), ApiError> {
+        let mut store = self.store.lock().unwrap();
+        for (id, order) in sections {
+            if let Some(section) = store.sections.iter_mut().find(|section| section.id == id) {
+                section.order = order;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_section(&self, section_id: String) -> Result<(), ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let len = store.sections.len();
+        store.sections.retain(|section| section.id != section_id);
+        if store.sections.len() == len {
+            return Err(not_found("Section", &section_id));
+        }
+        store
+            .tasks
+            .retain(|task| task.section_id.as_ref() != Some(&section_id));
+        Ok(())
+    }
+
+    async fn fetch_comments(&self, target: CommentTarget) -> Result<Vec<Comment>, ApiError> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .comments
+            .iter()
+            .filter(|comment| comment.target().as_ref() == Some(&target))
+            .cloned()
+            .collect())
+    }
+
+    async fn create_comment(&self, json: serde_json::Value) -> Result<Comment, ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let id = store.next_id();
+        let mut comment = Comment::from_json(id, &json);
+        comment.posted_uid = Some("memory".to_string());
+        store.count_comment(&comment, true);
+        store.comments.push(comment.clone());
+        Ok(comment)
+    }
+
+    async fn update_comment(
+        &self,
+        comment_id: String,
+        json: serde_json::Value,
+    ) -> Result<Comment, ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let comment = store
+            .comments
+            .iter_mut()
+            .find(|comment| comment.id == comment_id)
+            .ok_or_else(|| not_found("Comment", &comment_id))?;
+        if let Some(content) = json["content"].as_str() {
+            comment.content = content.to_string();
+        }
+        Ok(comment.clone())
+    }
+
+    async fn delete_comment(&self, comment_id: String) -> Result<(), ApiError> {
+        let mut store = self.store.lock().unwrap();
+        let Some(index) = store
+            .comments
+            .iter()
+            .position(|comment| comment.id == comment_id)
+        else {
+            return Err(not_found("Comment", &comment_id));
+        };
+        let comment = store.comments.remove(index);
+        store.count_comment(&comment, false);
+        Ok(())
+    }
+
+    /// There is no history to diff against, so every sync is a full one.
+    async fn sync(&self, _sync_token: String) -> Result<SyncDelta, ApiError> {
+        let store = self.store.lock().unwrap();
+        Ok(SyncDelta {
+            sync_token: format!("memory-{}", store.next_id),
+            full_sync: true,
+            projects: Changes {
+                updated: store.projects.clone(),
+                removed: Vec::new(),
+            },
+            tasks: Changes {
+                updated: store.tasks.clone(),
+                removed: Vec::new(),
+            },
+            sections: Changes {
+                updated: store.sections.clone(),
+                removed: Vec::new(),
+            },
+            labels: Changes {
+                updated: store.labels.clone(),
+                removed: Vec::new(),
+            },
+            filters: Changes {
+                updated: store.filters.clone(),
+                removed: Vec::new(),
+            },
+        })
+    }
+}
+
+fn not_found(kind: &str, id: &str) -> ApiError {
+    ApiError::Http {
+        status: 404,
+        body: format!("{} {} not found", kind, id),
+    }
+}
+
+fn new_task(store: &mut Store, json: &serde_json::Value) -> Task {
+    let id = store.next_id();
+    let mut task = Task::from_json(id, json);
+    task.order = store.tasks.len() as i32 + 1;
+    task.creator_id = "memory".to_string();
+    task
+}
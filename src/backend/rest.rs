@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    Client,
+};
+
+use crate::{
+    api_calls,
+    comments::{Comment, CommentTarget},
+    error::ApiError,
+    filters::SavedFilter,
+    labels::Label,
+    projects::{Collaborator, Project},
+    sections::Section,
+    sync::SyncDelta,
+    tasks::Task,
+};
+
+use super::TodoistBackend;
+
+/// Without a timeout a request lost on a flaky connection would never finish.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct RestBackend {
+    client: Client,
+    base_url: String,
+}
+
+impl RestBackend {
+    pub fn new(bearer_token: &str, base_url: String) -> RestBackend {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", bearer_token)).unwrap(),
+        );
+
+        let client = Client::builder()
+            .default_headers(headers)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap();
+
+        RestBackend { client, base_url }
+    }
+}
+
+impl TodoistBackend for RestBackend {
+    async fn fetch_projects(&self) -> Result<Vec<Project>, ApiError> {
+        api_calls::fetch_projects(&self.client, &self.base_url).await
+    }
+
+    async fn fetch_collaborators(&self, project_id: String) -> Result<Vec<Collaborator>, ApiError> {
+        api_calls::fetch_collaborators(&self.client, &self.base_url, &project_id).await
+    }
+
+    async fn fetch_tasks(&self) -> Result<Vec<Task>, ApiError> {
+        api_calls::fetch_tasks(&self.client, &self.base_url).await
+    }
+
+    async fn fetch_sections(&self) -> Result<Vec<Section>, ApiError> {
+        api_calls::fetch_sections(&self.client, &self.base_url).await
+    }
+
+    async fn fetch_labels(&self) -> Result<Vec<Label>, ApiError> {
+        api_calls::fetch_labels(&self.client, &self.base_url).await
+    }
+
+    async fn fetch_filters(&self) -> Result<Vec<SavedFilter>, ApiError> {
+        api_calls::fetch_filters(&self.client, &self.base_url).await
+    }
+
+    async fn create_task(&self, json: serde_json::Value) -> Result<Task, ApiError> {
+        api_calls::create_task(&self.client, &self.base_url, json).await
+    }
+
+    async fn update_task(
+        &self,
+        task_id: String,
+        json: serde_json::Value,
+    ) -> Result<Task, ApiError> {
+        api_calls::update_task(&self.client, &self.base_url, json, task_id).await
+    }
+
+    async fn move_task(
+        &self,
+        task_id: String,
+        project_id: String,
+        section_id: Option<String>,
+    ) -> Result<(), ApiError> {
+        api_calls::move_task(
+            &self.client,
+            &self.base_url,
+            task_id,
+            project_id,
+            section_id,
+        )
+        .await
+    }
+
+    async fn close_task(&self, task_id: String) -> Result<(), ApiError> {
+        api_calls::close_task(&self.client, &self.base_url, task_id).await
+    }
+
+    async fn delete_task(&self, task_id: String) -> Result<(), ApiError> {
+        api_calls::delete_task(&self.client, &self.base_url, task_id).await
+    }
+
+    async fn create_project(&self, json: serde_json::Value) -> Result<Project, ApiError> {
+        api_calls::create_project(&self.client, &self.base_url, json).await
+    }
+
+    async fn update_project(
+        &self,
+        project_id: String,
+        json: serde_json::Value,
+    ) -> Result<Project, ApiError> {
+        api_calls::update_project(&self.client, &self.base_url, json, project_id).await
+    }
+
+    async fn move_project(
+        &self,
+        project_id: String,
+        parent_id: Option<String>,
+    ) -> Result<(), ApiError> {
+        api_calls::move_project(&self.client, &self.base_url, project_id, parent_id).await
+    }
+
+    async fn archive_project(&self, project_id: String) -> Result<(), ApiError> {
+        api_calls::archive_project(&self.client, &self.base_url, project_id).await
+    }
+
+    async fn delete_project(&self, project_id: String) -> Result<(), ApiError> {
+        api_calls::delete_project(&self.client, &self.base_url, project_id).await
+    }
+
+    async fn create_section(&self, json: serde_json::Value) -> Result<Section, ApiError> {
+        api_calls::create_section(&self.client, &self.base_url, json).await
+    }
+
+    async fn update_section(
+        &self,
+        section_id: String,
+        json: serde_json::Value,
+    ) -> Result<Section, ApiError> {
+        api_calls::update_section(&self.client, &self.base_url, json, section_id).await
+    }
+
+    async fn reorder_sections(&self, sections: Vec<(String, i32)>) -> Result<(), ApiError> {
+        api_calls::reorder_sections(&self.client, &self.base_url, sections).await
+    }
+
+    async fn delete_section(&self, section_id: String) -> Result<(), ApiError> {
+        api_calls::delete_section(&self.client, &self.base_url, section_id).await
+    }
+
+    async fn fetch_comments(&self, target: CommentTarget) -> Result<Vec<Comment>, ApiError> {
+        api_calls::fetch_comments(&self.client, &self.base_url, &target).await
+    }
+
+    async fn create_comment(&self, json: serde_json::Value) -> Result<Comment, ApiError> {
+        api_calls::create_comment(&self.client, &self.base_url, json).await
+    }
+
+    async fn update_comment(
+        &self,
+        comment_id: String,
+        json: serde_json::Value,
+    ) -> Result<Comment, ApiError> {
+        api_calls::update_comment(&self.client, &self.base_url, json, comment_id).await
+    }
+
+    async fn delete_comment(&self, comment_id: String) -> Result<(), ApiError> {
+        api_calls::delete_comment(&self.client, &self.base_url, comment_id).await
+    }
+
+    async fn sync(&self, sync_token: String) -> Result<SyncDelta, ApiError> {
+        api_calls::sync(&self.client, &self.base_url, &sync_token).await
+    }
+}
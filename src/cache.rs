@@ -0,0 +1,66 @@
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config, filters::SavedFilter, labels::Label, projects::Project, sections::Section, tasks::Task,
+    App,
+};
+
+/// The last data we successfully fetched, so the app has something to show
+/// before the network answers, or when it never does.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cache {
+    pub projects: Vec<Project>,
+    pub tasks: Vec<Task>,
+    pub sections: Vec<Section>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    /// The account's filters, the configured ones aren't cached.
+    #[serde(default)]
+    pub filters: Vec<SavedFilter>,
+    /// Unix timestamp of the fetch the data came from.
+    pub saved_at: i64,
+    /// Lets the next sync ask only for what changed since this data.
+    #[serde(default)]
+    pub sync_token: Option<String>,
+}
+
+impl Cache {
+    pub fn from_app(app: &App) -> Cache {
+        Cache {
+            projects: app.projects.projects.clone(),
+            tasks: app.tasks.tasks.clone(),
+            sections: app.sections.sections.clone(),
+            labels: app.labels.labels.clone(),
+            filters: app.filters.account().to_vec(),
+            saved_at: app.last_synced.map_or(0, |time| time.timestamp()),
+            sync_token: app.sync_token.clone(),
+        }
+    }
+
+    pub fn saved_at(&self) -> Option<DateTime<Local>> {
+        DateTime::from_timestamp(self.saved_at, 0).map(|time| time.with_timezone(&Local))
+    }
+
+    pub fn default_path() -> PathBuf {
+        config::config_dir_path().join("cache.json")
+    }
+
+    /// Returns `None` when there is no cache yet or it can't be read, e.g.
+    /// after the models changed shape.
+    pub fn load(path: &Path) -> Option<Cache> {
+        let file = File::open(path).ok()?;
+        serde_json::from_reader(BufReader::new(file)).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string(self)?;
+        config::write_atomically(path, &json)
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+
+/// The month shown in the calendar pane and the day highlighted in it.
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    pub selected: NaiveDate,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Calendar {
+            selected: Local::now().date_naive(),
+        }
+    }
+}
+
+impl Calendar {
+    /// Moves the highlight by days, crossing into other months as needed.
+    pub fn move_days(&mut self, days: i64) {
+        let moved = if days >= 0 {
+            self.selected.checked_add_days(Days::new(days as u64))
+        } else {
+            self.selected
+                .checked_sub_days(Days::new(days.unsigned_abs()))
+        };
+        if let Some(moved) = moved {
+            self.selected = moved;
+        }
+    }
+
+    /// Moves the highlight to the same day of another month, or its last
+    /// day when the month is shorter.
+    pub fn move_months(&mut self, forward: bool) {
+        let moved = if forward {
+            self.selected.checked_add_months(Months::new(1))
+        } else {
+            self.selected.checked_sub_months(Months::new(1))
+        };
+        if let Some(moved) = moved {
+            self.selected = moved;
+        }
+    }
+
+    /// The weeks of the shown month, Monday first, `None` for days of the
+    /// neighbouring months.
+    pub fn weeks(&self) -> Vec<[Option<NaiveDate>; 7]> {
+        let first = self.selected.with_day(1).unwrap_or(self.selected);
+        let offset = first.weekday().num_days_from_monday() as usize;
+
+        let mut weeks = Vec::new();
+        let mut week = [None; 7];
+        for (i, date) in first
+            .iter_days()
+            .take_while(|date| date.month() == first.month())
+            .enumerate()
+        {
+            let position = i + offset;
+            week[position % 7] = Some(date);
+            if position % 7 == 6 {
+                weeks.push(week);
+                week = [None; 7];
+            }
+        }
+        if week.iter().any(Option::is_some) {
+            weeks.push(week);
+        }
+        weeks
+    }
+}
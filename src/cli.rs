@@ -0,0 +1,501 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt::Display,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    process,
+};
+
+use crate::{
+    backend::TodoistBackend,
+    export::{Export, ExportFormat},
+    import::{Import, ImportFormat},
+    projects::Projects,
+    query::Query,
+    quick_add::{self, QuickAdd},
+    tasks::{self, Filter, Task, TaskRow, Tasks},
+};
+
+pub const USAGE: &str = "Usage: todoist [COMMAND]
+
+Without a command the interface opens.
+
+Commands:
+  add <TEXT>                      Add a task, e.g. \"Buy milk tomorrow p1 #Home /Shop @errands\"
+  list [--filter QUERY] [--format text|json]
+                                  List the tasks matching a filter, today's by default
+  done <ID>...                    Complete tasks
+  projects [--format text|json]   List the projects
+  export [--filter QUERY | --all] [--format json|csv|markdown] [--output FILE]
+                                  Export the tasks matching a filter, or all of them,
+                                  as JSON unless the format or file says otherwise
+  import <FILE> [--project NAME] [--format markdown|csv|todotxt] [--dry-run]
+                                  Add the tasks of a Markdown checklist, CSV or todo.txt
+                                  file to a project, the inbox by default
+  help                            Show this message";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Add {
+        text: String,
+        format: Format,
+    },
+    List {
+        filter: Option<String>,
+        format: Format,
+    },
+    Done {
+        ids: Vec<String>,
+    },
+    Projects {
+        format: Format,
+    },
+    Export {
+        filter: Option<String>,
+        /// Every task, under its project, instead of a filter's.
+        all: bool,
+        format: ExportFormat,
+        /// Printed when missing.
+        output: Option<PathBuf>,
+    },
+    Import {
+        path: PathBuf,
+        /// The inbox when missing.
+        project: Option<String>,
+        format: ImportFormat,
+        /// Only shows what would be created.
+        dry_run: bool,
+    },
+    Help,
+}
+
+impl Command {
+    /// Reads the arguments after the program name.
+    pub fn parse(args: &[String]) -> Result<Command, String> {
+        let Some((command, args)) = args.split_first() else {
+            return Ok(Command::Help);
+        };
+        let mut filter = None;
+        let mut format_name = None;
+        let mut all = false;
+        let mut output = None;
+        let mut project = None;
+        let mut dry_run = false;
+        let mut positional = Vec::new();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} needs a value", arg))
+            };
+            match arg.as_str() {
+                "--filter" | "-f" if matches!(command.as_str(), "list" | "ls" | "export") => {
+                    filter = Some(value()?)
+                }
+                "--format" => format_name = Some(value()?),
+                "--json" => format_name = Some("json".to_string()),
+                "--all" if command == "export" => all = true,
+                "--output" | "-o" if command == "export" => output = Some(PathBuf::from(value()?)),
+                "--project" | "-p" if command == "import" => project = Some(value()?),
+                "--dry-run" | "-n" if command == "import" => dry_run = true,
+                other if other.starts_with("--") => {
+                    return Err(format!("Unknown option {}", other))
+                }
+                other => positional.push(other.to_string()),
+            }
+        }
+        if let Some(filter) = &filter {
+            Query::parse(filter).map_err(|e| format!("Invalid filter \"{}\": {}", filter, e))?;
+        }
+
+        let format = || match format_name.as_deref() {
+            None | Some("text") => Ok(Format::Text),
+            Some("json") => Ok(Format::Json),
+            Some(other) => Err(format!("Unknown format \"{}\"", other)),
+        };
+        let no_arguments = |command: Command| {
+            if positional.is_empty() {
+                Ok(command)
+            } else {
+                Err(format!("Unexpected argument \"{}\"", positional[0]))
+            }
+        };
+        match command.as_str() {
+            "add" if positional.is_empty() => Err("add needs the task to add".to_string()),
+            "add" => Ok(Command::Add {
+                text: positional.join(" "),
+                format: format()?,
+            }),
+            "list" | "ls" => no_arguments(Command::List {
+                filter,
+                format: format()?,
+            }),
+            "done" | "close" if positional.is_empty() => {
+                Err("done needs the ids of the tasks".to_string())
+            }
+            "done" | "close" => Ok(Command::Done { ids: positional }),
+            "projects" => no_arguments(Command::Projects { format: format()? }),
+            "export" if all && filter.is_some() => {
+                Err("export takes either --filter or --all".to_string())
+            }
+            "export" => {
+                let format = match &format_name {
+                    Some(name) => ExportFormat::parse(name)
+                        .ok_or_else(|| format!("Unknown format \"{}\"", name))?,
+                    None => output
+                        .as_deref()
+                        .and_then(ExportFormat::from_path)
+                        .unwrap_or(ExportFormat::Json),
+                };
+                no_arguments(Command::Export {
+                    filter,
+                    all,
+                    format,
+                    output,
+                })
+            }
+            "import" if positional.len() != 1 => {
+                Err("import needs the file to import from".to_string())
+            }
+            "import" => {
+                let path = PathBuf::from(&positional[0]);
+                let format = match &format_name {
+                    Some(name) => ImportFormat::parse(name)
+                        .ok_or_else(|| format!("Unknown format \"{}\"", name))?,
+                    None => ImportFormat::from_path(&path).ok_or_else(|| {
+                        format!(
+                            "Can't tell the format of {}, pass --format markdown, csv or todotxt",
+                            path.display()
+                        )
+                    })?,
+                };
+                Ok(Command::Import {
+                    path,
+                    project,
+                    format,
+                    dry_run,
+                })
+            }
+            "help" | "--help" | "-h" => Ok(Command::Help),
+            other => Err(format!("Unknown command \"{}\"", other)),
+        }
+    }
+}
+
+/// Runs a command, printing what it did to stdout.
+pub async fn run<B: TodoistBackend>(backend: &B, command: Command) -> Result<(), String> {
+    match command {
+        Command::Add { text, format } => {
+            let (projects, sections) =
+                tokio::try_join!(backend.fetch_projects(), backend.fetch_sections())
+                    .map_err(|e| e.to_string())?;
+            let quick_add = QuickAdd::parse(&text, &projects, &sections);
+            if quick_add.content.trim().is_empty() {
+                return Err(format!("\"{}\" has no text left for the task", text));
+            }
+            // only a shared project has anyone to assign to
+            let shared_project = quick_add
+                .project
+                .as_deref()
+                .and_then(|name| quick_add::find_project(&projects, name))
+                .filter(|project| project.is_shared);
+            let collaborators = match (&quick_add.assignee, shared_project) {
+                (Some(_), Some(project)) => backend
+                    .fetch_collaborators(project.id.clone())
+                    .await
+                    .map_err(|e| e.to_string())?,
+                _ => Vec::new(),
+            };
+            // like in Todoist, tasks without a project go to the inbox
+            let inbox = projects.iter().find(|project| project.is_inbox_project);
+            let resolved = quick_add.resolve(
+                &projects,
+                &sections,
+                Some(&collaborators),
+                inbox.map(|inbox| inbox.id.as_str()),
+            );
+            let json = quick_add.to_json(&resolved)?;
+            let task = backend.create_task(json).await.map_err(|e| e.to_string())?;
+            for hint in &resolved.hints {
+                eprintln!("todoist: {}", hint);
+            }
+            match format {
+                Format::Text => print(task_line(&task, 0)),
+                Format::Json => print_json(&task)?,
+            }
+        }
+        Command::List { filter, format } => {
+            let mut tasks = fetch_tasks(backend, filter).await?;
+            // subtasks are listed under their parents
+            tasks.expanded_tasks = tasks.tasks.iter().map(|task| task.id.clone()).collect();
+            tasks.filter_task_list();
+            let listed = listed(&tasks);
+            match format {
+                Format::Text => {
+                    for task in listed {
+                        print(task_line(task, tasks.depth(task)));
+                    }
+                }
+                Format::Json => print_json(&listed)?,
+            }
+        }
+        Command::Done { ids } => {
+            for id in ids {
+                backend
+                    .close_task(id.clone())
+                    .await
+                    .map_err(|e| format!("Could not complete {}: {}", id, e))?;
+                print(format!("Completed {}", id));
+            }
+        }
+        Command::Projects { format } => {
+            let projects = backend.fetch_projects().await.map_err(|e| e.to_string())?;
+            let projects = Projects::new(projects);
+            let listed: Vec<_> = projects
+                .display_projects
+                .iter()
+                .map(|index| &projects.projects[*index])
+                .collect();
+            match format {
+                Format::Text => {
+                    for project in listed {
+                        let indent = "  ".repeat(projects.depth(project));
+                        print(format!("{}\t{}{}", project.id, indent, project.name));
+                    }
+                }
+                Format::Json => print_json(&listed)?,
+            }
+        }
+        Command::Export {
+            filter,
+            all,
+            format,
+            output,
+        } => {
+            let title = filter.clone().unwrap_or_else(|| "Today".to_string());
+            let tasks = fetch_tasks(backend, filter).await?;
+            let export = if all {
+                Export::account(&tasks)
+            } else {
+                Export::view(&title, &tasks)
+            };
+            let contents = export.write(format)?;
+            match output {
+                Some(path) => {
+                    fs::write(&path, contents)
+                        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+                    print(format!(
+                        "Exported {} tasks to {}",
+                        export.task_count(),
+                        path.display()
+                    ));
+                }
+                None => print(contents.trim_end()),
+            }
+        }
+        Command::Import {
+            path,
+            project,
+            format,
+            dry_run,
+        } => {
+            let import = Import::read(&path, format)?;
+            let (projects, sections) =
+                tokio::try_join!(backend.fetch_projects(), backend.fetch_sections())
+                    .map_err(|e| e.to_string())?;
+            let project = match &project {
+                Some(name) => quick_add::find_project(&projects, name)
+                    .ok_or_else(|| format!("There is no project called \"{}\"", name))?,
+                None => projects
+                    .iter()
+                    .find(|project| project.is_inbox_project)
+                    .ok_or("There is no inbox to import to, pass --project".to_string())?,
+            };
+            let mut section_ids: HashMap<String, String> = sections
+                .iter()
+                .filter(|section| section.project_id == project.id)
+                .map(|section| (section.name.to_lowercase(), section.id.clone()))
+                .collect();
+
+            if dry_run {
+                print(format!("Would add {}", import.describe(&project.name)));
+                for (index, draft) in import.drafts.iter().enumerate() {
+                    if import.starts_section(index) {
+                        let heading = match &draft.section {
+                            Some(name) if !section_ids.contains_key(&name.to_lowercase()) => {
+                                format!("{} (new section)", name)
+                            }
+                            Some(name) => name.clone(),
+                            None => "No section".to_string(),
+                        };
+                        print(format!("\n{}", heading));
+                    }
+                    let indent = "  ".repeat(import.depth(index));
+                    print(format!("{}- {}", indent, draft.summary()));
+                }
+                return Ok(());
+            }
+
+            for name in import.section_names() {
+                if let Entry::Vacant(entry) = section_ids.entry(name.to_lowercase()) {
+                    let json = serde_json::json!({ "name": name, "project_id": project.id });
+                    let section = backend
+                        .create_section(json)
+                        .await
+                        .map_err(|e| format!("Could not create section \"{}\": {}", name, e))?;
+                    entry.insert(section.id);
+                }
+            }
+            // the ids Todoist gave the drafts created so far, for their subtasks
+            let mut ids: Vec<String> = Vec::new();
+            for (index, draft) in import.drafts.iter().enumerate() {
+                let section_id = draft
+                    .section
+                    .as_ref()
+                    .and_then(|name| section_ids.get(&name.to_lowercase()));
+                let parent_id = draft.parent.map(|parent| ids[parent].as_str());
+                let json = draft.to_json(&project.id, section_id.map(|id| id.as_str()), parent_id);
+                let task = backend.create_task(json).await.map_err(|e| {
+                    format!(
+                        "Could not create \"{}\" after adding {} of {} tasks: {}",
+                        draft.content,
+                        index,
+                        import.drafts.len(),
+                        e
+                    )
+                })?;
+                print(task_line(&task, import.depth(index)));
+                ids.push(task.id);
+            }
+        }
+        Command::Help => print(USAGE),
+    }
+    Ok(())
+}
+
+/// Every task along with the projects and sections, filtered by the query
+/// or to today's.
+async fn fetch_tasks<B: TodoistBackend>(
+    backend: &B,
+    filter: Option<String>,
+) -> Result<Tasks, String> {
+    let (projects, tasks, sections) = tokio::try_join!(
+        backend.fetch_projects(),
+        backend.fetch_tasks(),
+        backend.fetch_sections(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut tasks = Tasks::new(tasks);
+    tasks.set_projects(&projects);
+    tasks.set_sections(&sections);
+    if let Some(filter) = filter {
+        tasks.filter = Filter::Query(filter);
+    }
+    Ok(tasks)
+}
+
+/// Tab separated id, priority, due date and content, the content indented
+/// by `depth`.
+fn task_line(task: &Task, depth: usize) -> String {
+    let due = task
+        .due
+        .as_ref()
+        .map_or("-".to_string(), |due| due.date.to_string());
+    format!(
+        "{}\t{}\t{}\t{}{}",
+        task.id,
+        tasks::priority_name(task.priority),
+        due,
+        "  ".repeat(depth),
+        task.content
+    )
+}
+
+/// Prints a line, quietly stopping when stdout is closed, e.g. piped into
+/// `head`.
+pub fn print(text: impl Display) {
+    if writeln!(io::stdout(), "{}", text).is_err() {
+        process::exit(0);
+    }
+}
+
+/// The tasks in the order of the rows, once each. Expanded tasks bring
+/// along all their subtasks, only the matching ones are kept.
+fn listed(tasks: &Tasks) -> Vec<&Task> {
+    let mut seen = HashSet::new();
+    tasks
+        .rows
+        .iter()
+        .filter_map(|row| match row {
+            TaskRow::Task(index) => Some(&tasks.tasks[*index]),
+            _ => None,
+        })
+        .filter(|task| tasks.matches_filter(task) && seen.insert(task.id.as_str()))
+        .collect()
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    print(json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        Command::parse(&args)
+    }
+
+    #[test]
+    fn rejects_an_invalid_filter() {
+        for filter in ["p1 &", "bogus thing", "(today"] {
+            assert!(parse(&["list", "--filter", filter]).is_err(), "{}", filter);
+            assert!(parse(&["export", "-f", filter]).is_err(), "{}", filter);
+        }
+    }
+
+    #[test]
+    fn keeps_a_valid_filter() {
+        assert_eq!(
+            parse(&["ls", "--filter", "p1 | overdue", "--json"]),
+            Ok(Command::List {
+                filter: Some("p1 | overdue".to_string()),
+                format: Format::Json,
+            })
+        );
+    }
+
+    #[test]
+    fn lists_only_matching_subtasks() {
+        let task = |id: &str, parent_id: Option<&str>, content: &str| {
+            Task::from_json(
+                id.to_string(),
+                &serde_json::json!({ "parent_id": parent_id, "content": content }),
+            )
+        };
+        let mut tasks = Tasks::new(vec![
+            task("1", None, "Pack"),
+            task("2", Some("1"), "Pack socks"),
+            task("3", Some("1"), "Book a train"),
+        ]);
+        tasks.filter = Filter::Query("search: pack".to_string());
+        tasks.expanded_tasks = ["1".to_string()].into();
+        tasks.filter_task_list();
+
+        let ids: Vec<&str> = listed(&tasks).iter().map(|task| task.id.as_str()).collect();
+        assert_eq!(ids, ["1", "2"]);
+    }
+}
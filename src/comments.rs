@@ -0,0 +1,177 @@
+use chrono::{DateTime, Local};
+use ratatui::widgets::ListState;
+use serde::{Deserialize, Serialize};
+
+use crate::projects::Collaborator;
+
+/// What a comment is attached to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommentTarget {
+    Task(String),
+    Project(String),
+}
+
+impl CommentTarget {
+    /// The query parameter, or JSON field, naming the target.
+    pub fn param(&self) -> (&'static str, &str) {
+        match self {
+            CommentTarget::Task(id) => ("task_id", id),
+            CommentTarget::Project(id) => ("project_id", id),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Comment {
+    pub id: String,
+    #[serde(default)]
+    pub task_id: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    pub content: String,
+    pub posted_at: String,
+    /// Who posted it, the REST API leaves it out.
+    #[serde(default)]
+    pub posted_uid: Option<String>,
+}
+
+impl Comment {
+    /// Builds a comment locally from the JSON used to create it, for when
+    /// Todoist hasn't answered yet.
+    pub fn from_json(id: String, json: &serde_json::Value) -> Comment {
+        let string_field = |key: &str| json[key].as_str().map(|s| s.to_string());
+        Comment {
+            id,
+            task_id: string_field("task_id"),
+            project_id: string_field("project_id"),
+            content: string_field("content").unwrap_or_default(),
+            posted_at: Local::now().to_rfc3339(),
+            posted_uid: None,
+        }
+    }
+
+    pub fn target(&self) -> Option<CommentTarget> {
+        match (&self.task_id, &self.project_id) {
+            (Some(task_id), _) => Some(CommentTarget::Task(task_id.clone())),
+            (None, Some(project_id)) => Some(CommentTarget::Project(project_id.clone())),
+            (None, None) => None,
+        }
+    }
+
+    /// The name of who posted it, when they are among `collaborators`.
+    pub fn author<'a>(
+        &self,
+        collaborators: impl IntoIterator<Item = &'a Collaborator>,
+    ) -> Option<&'a str> {
+        let uid = self.posted_uid.as_deref()?;
+        collaborators
+            .into_iter()
+            .find(|collaborator| collaborator.id == uid)
+            .map(|collaborator| collaborator.name.as_str())
+    }
+
+    /// When the comment was posted, in local time.
+    pub fn posted_at(&self) -> String {
+        DateTime::parse_from_rfc3339(&self.posted_at)
+            .map(|posted_at| {
+                posted_at
+                    .with_timezone(&Local)
+                    .format("%d %b, %Y %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.posted_at.clone())
+    }
+}
+
+/// The comments of the task being edited, or of a project.
+#[derive(Debug, Default)]
+pub struct Comments {
+    pub target: Option<CommentTarget>,
+    /// Oldest first.
+    pub comments: Vec<Comment>,
+    pub state: ListState,
+    /// Fetched yet, until then there is nothing to show.
+    pub loaded: bool,
+    /// Why they couldn't be fetched.
+    pub error: Option<String>,
+    /// Set when the comments should be fetched, the main loop does it.
+    pub fetch: bool,
+}
+
+impl Comments {
+    pub fn new(target: CommentTarget) -> Comments {
+        Comments {
+            target: Some(target),
+            fetch: true,
+            ..Default::default()
+        }
+    }
+
+    /// Takes fetched comments, when they are still the ones wanted.
+    pub fn set_comments(&mut self, target: &CommentTarget, mut comments: Vec<Comment>) {
+        if self.target.as_ref() != Some(target) {
+            return;
+        }
+        let highlighted = self.selected().map(|comment| comment.id.clone());
+        comments.sort_by(|a, b| a.posted_at.cmp(&b.posted_at));
+        self.comments = comments;
+        self.loaded = true;
+        self.select_id(highlighted.as_deref());
+    }
+
+    pub fn next(&mut self) {
+        if self.comments.is_empty() {
+            self.state.select(None);
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i + 1 < self.comments.len() => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        if self.comments.is_empty() {
+            self.state.select(None);
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(0) | None => self.comments.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn selected(&self) -> Option<&Comment> {
+        self.state.selected().and_then(|i| self.comments.get(i))
+    }
+
+    pub fn select_id(&mut self, id: Option<&str>) {
+        let index = id.and_then(|id| self.comments.iter().position(|comment| comment.id == id));
+        self.state.select(index);
+    }
+
+    /// Points everything at the id Todoist gave a task or project created
+    /// offline.
+    pub fn replace_id(&mut self, temp_id: &str, id: &str) {
+        for comment in &mut self.comments {
+            for value in [comment.task_id.as_mut(), comment.project_id.as_mut()]
+                .into_iter()
+                .flatten()
+            {
+                if value == temp_id {
+                    *value = id.to_string();
+                }
+            }
+        }
+        match &mut self.target {
+            Some(CommentTarget::Task(target) | CommentTarget::Project(target))
+                if target == temp_id =>
+            {
+                *target = id.to_string();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use dirs::config_dir;
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, stdin, BufReader},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{banner, filters::SavedFilter};
+
+pub const DEFAULT_BASE_URL: &str = "https://api.todoist.com";
+
+/// Environment variable that takes precedence over `base_url` in the config file.
+pub const BASE_URL_ENV: &str = "TODOIST_BASE_URL";
+
+/// Environment variable that takes precedence over `backend` in the config file.
+pub const BACKEND_ENV: &str = "TODOIST_BACKEND";
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    #[default]
+    Rest,
+    Memory,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Config {
+    pub bearer_token: String,
+    /// Root of the Todoist API, e.g. `http://localhost:8080` for a local mock server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// `memory` runs against an in-process store instead of the API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<BackendKind>,
+    /// Seconds between automatic syncs, off when missing or 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_interval: Option<u64>,
+    /// Views listed under the projects, `SavedFilter::defaults` when missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Vec<SavedFilter>>,
+    /// Also list the filters saved in the Todoist account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_filters: Option<bool>,
+    /// How many days the upcoming view covers, a week when missing and a
+    /// year at most.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upcoming_days: Option<u32>,
+}
+
+impl Config {
+    pub fn base_url(&self) -> String {
+        let base_url = env::var(BASE_URL_ENV)
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .or_else(|| self.base_url.clone())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        base_url.trim().trim_end_matches('/').to_string()
+    }
+
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        self.refresh_interval
+            .filter(|seconds| *seconds > 0)
+            .map(Duration::from_secs)
+    }
+
+    pub fn saved_filters(&self) -> Result<Vec<SavedFilter>, String> {
+        let filters = self.filters.clone().unwrap_or_else(SavedFilter::defaults);
+        SavedFilter::validate(&filters)?;
+        Ok(filters)
+    }
+
+    pub fn sync_filters(&self) -> bool {
+        self.sync_filters.unwrap_or(false)
+    }
+
+    pub fn upcoming_days(&self) -> u32 {
+        self.upcoming_days.unwrap_or(7).clamp(1, 366)
+    }
+
+    pub fn backend(&self) -> BackendKind {
+        match env::var(BACKEND_ENV).ok().as_deref().map(str::trim) {
+            Some("memory") => BackendKind::Memory,
+            Some("rest") => BackendKind::Rest,
+            _ => self.backend.unwrap_or_default(),
+        }
+    }
+}
+
+/// Writes the file through a temporary one next to it, so a crash halfway
+/// leaves the old contents rather than half of the new ones.
+pub fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)
+}
+
+pub fn config_dir_path() -> PathBuf {
+    match config_dir() {
+        Some(home) => {
+            let config_dir = home.join(".todoist");
+            if !config_dir.exists() {
+                fs::create_dir(&config_dir).unwrap();
+            }
+            config_dir
+        }
+        None => panic!("No directory found"),
+    }
+}
+
+/// The saved config, without printing anything or asking for a token when
+/// there is none, for the command line.
+pub fn saved_config() -> Option<Config> {
+    let file = File::open(config_dir_path().join("config.json")).ok()?;
+    serde_json::from_reader(BufReader::new(file)).ok()
+}
+
+pub fn get_config() -> Config {
+    let config_file_path = &config_dir_path().join("config.json");
+
+    if config_file_path.exists() {
+        let file = File::open(config_file_path).unwrap();
+        let reader = BufReader::new(file);
+
+        let config: Config = serde_json::from_reader(reader).unwrap();
+
+        println!("Your config is saved at {}", config_file_path.display());
+        config
+    } else {
+        println!("{}", banner::BANNER);
+
+        println!();
+
+        println!("\nHow to get setup:");
+        println!("-----------------\n");
+
+        let instructions = [
+            "Go to the todoist integrations - https://app.todoist.com/app/settings/integrations/developer",
+            "Under the developer tab, you will be able to see the API Token",
+            "Copy the token and paste it below",
+            "You are now ready to authenticate with Todoist!",
+            &format!("Config will be saved to {}\n\n", config_file_path.display()),
+        ];
+
+        for (number, item) in (1..).zip(instructions.iter()) {
+            println!("  {}. {}", number, item);
+        }
+
+        let mut client_key = String::new();
+        println!("\nEnter your API token:");
+        stdin().read_line(&mut client_key).unwrap();
+        client_key = client_key.trim().to_string(); // Trim the newline character
+
+        let config = Config {
+            bearer_token: client_key,
+            base_url: None,
+            backend: None,
+            refresh_interval: None,
+            filters: None,
+            sync_filters: None,
+            upcoming_days: None,
+        };
+
+        let config_json = serde_json::to_string(&config).unwrap();
+        fs::write(config_file_path, config_json).unwrap();
+        config
+    }
+}
@@ -0,0 +1,20 @@
+/// A destructive action waiting for the user to say yes.
+#[derive(Debug, Clone)]
+pub struct Confirmation {
+    pub message: String,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    ArchiveProject(String),
+    DeleteProject(String),
+    DeleteSection(String),
+    DeleteComment(String),
+}
+
+impl Confirmation {
+    pub fn new(message: String, action: Action) -> Confirmation {
+        Confirmation { message, action }
+    }
+}
@@ -1,4 +1,4 @@
-use std::panic;
+use std::{fmt, panic};
 
 use color_eyre::{config::HookBuilder, eyre};
 
@@ -25,3 +25,79 @@ pub fn install_hooks() -> color_eyre::Result<()> {
 
     Ok(())
 }
+
+/// Everything that can go wrong while talking to Todoist.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// The request never got a response.
+    Network(String),
+    /// 401, almost always an incorrect token, or 403 when the token can't
+    /// touch what was asked for, e.g. a shared project it was removed from.
+    Unauthorized(u16),
+    /// 429, with the number of seconds Todoist asked us to wait if it said so.
+    RateLimited { retry_after: Option<u64> },
+    /// 5xx.
+    Server { status: u16, body: String },
+    /// Any other unsuccessful status.
+    Http { status: u16, body: String },
+    /// The response did not have the shape we expected.
+    Decode { message: String, snippet: String },
+}
+
+impl ApiError {
+    /// Whether trying the same request again later could succeed. A 401
+    /// can, once the token is fixed; a 403 won't.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ApiError::Network(_)
+                | ApiError::RateLimited { .. }
+                | ApiError::Server { .. }
+                | ApiError::Unauthorized(401)
+        )
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Network(message) => write!(f, "Network error: {}", message),
+            ApiError::Unauthorized(403) => write!(
+                f,
+                "Received a 403 error. The token doesn't have access to this, e.g. a project that is no longer shared with you."
+            ),
+            ApiError::Unauthorized(status) => write!(
+                f,
+                "Received a {} error. This would most likely be because of an incorrect token. Check your config file for token.",
+                status
+            ),
+            ApiError::RateLimited {
+                retry_after: Some(seconds),
+            } => write!(
+                f,
+                "Todoist is rate limiting requests, try again in {} seconds",
+                seconds
+            ),
+            ApiError::RateLimited { retry_after: None } => {
+                write!(f, "Todoist is rate limiting requests, try again later")
+            }
+            ApiError::Server { status, body } => {
+                write!(f, "Todoist server error: {} \n\n {}", status, body)
+            }
+            ApiError::Http { status, body } => write!(f, "API error: {} \n\n {}", status, body),
+            ApiError::Decode { message, snippet } => write!(
+                f,
+                "Failed to parse response: {} (Response was: {})",
+                message, snippet
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(error: reqwest::Error) -> Self {
+        ApiError::Network(error.to_string())
+    }
+}
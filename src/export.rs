@@ -0,0 +1,467 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    projects::Projects,
+    tasks::{self, Task, TaskRow, Tasks},
+    tree,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// The tasks as the API returns them.
+    Json,
+    /// A row per task, nesting left to `parent_id`.
+    Csv,
+    /// A checklist under section headers, subtasks indented.
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<ExportFormat> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            _ => None,
+        }
+    }
+
+    /// Going by the file's extension.
+    pub fn from_path(path: &Path) -> Option<ExportFormat> {
+        ExportFormat::parse(path.extension()?.to_str()?)
+    }
+}
+
+/// The path with a leading `~/` pointing into the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// A line of an export.
+#[derive(Debug)]
+enum Entry {
+    /// A project, section or day, `level` 2 being the outermost.
+    Heading { level: usize, text: String },
+    /// Nested `depth` levels below a task that isn't a subtask.
+    Task { task: Box<Task>, depth: usize },
+}
+
+/// Tasks in the order they are shown, ready to be written out.
+#[derive(Debug)]
+pub struct Export {
+    title: String,
+    entries: Vec<Entry>,
+    /// Names of projects and sections, by id.
+    project_names: HashMap<String, String>,
+    section_names: HashMap<String, String>,
+}
+
+impl Export {
+    fn new(title: &str, tasks: &Tasks) -> Export {
+        Export {
+            title: title.to_string(),
+            entries: Vec::new(),
+            project_names: tasks
+                .projects
+                .iter()
+                .map(|project| (project.id.clone(), project.name.clone()))
+                .collect(),
+            section_names: tasks
+                .sections
+                .iter()
+                .map(|section| (section.id.clone(), section.name.clone()))
+                .collect(),
+        }
+    }
+
+    /// The tasks of the view `tasks` is filtered to, with all their
+    /// subtasks, under the view's section or day headers. Collapsed
+    /// sections are included.
+    pub fn view(title: &str, tasks: &Tasks) -> Export {
+        let mut unfolded = Tasks::new(tasks.tasks.clone());
+        unfolded.filter = tasks.filter.clone();
+        unfolded.subproject_ids = tasks.subproject_ids.clone();
+        unfolded.set_projects(&tasks.projects);
+        unfolded.set_sections(&tasks.sections);
+        unfolded.filter_task_list();
+        let tasks = &unfolded;
+
+        let mut export = Export::new(title, tasks);
+        let listed: HashSet<usize> = tasks.display_tasks.iter().copied().collect();
+        for row in &tasks.rows {
+            match row {
+                TaskRow::Section(section) => export.entries.push(Entry::Heading {
+                    level: 2,
+                    text: tasks.sections[*section].name.clone(),
+                }),
+                TaskRow::Day(date) => export.entries.push(Entry::Heading {
+                    level: 2,
+                    text: date.format("%A %-d %B").to_string(),
+                }),
+                TaskRow::Task(index) if listed.contains(index) => {
+                    export.push_task(tasks, *index, 0);
+                }
+                TaskRow::Task(_) => {}
+            }
+        }
+        export
+    }
+
+    /// Every task, under its project and section.
+    pub fn account(tasks: &Tasks) -> Export {
+        let mut export = Export::new("All tasks", tasks);
+        let ids: HashSet<&str> = tasks.tasks.iter().map(|task| task.id.as_str()).collect();
+        let top_level = |project_id: &str, section_id: Option<&str>| {
+            let mut indices: Vec<usize> = (0..tasks.tasks.len())
+                .filter(|index| {
+                    let task = &tasks.tasks[*index];
+                    let in_section = match section_id {
+                        Some(section_id) => task.section_id.as_deref() == Some(section_id),
+                        None => task.section_id.as_ref().is_none_or(|id| {
+                            !tasks.sections.iter().any(|section| section.id == *id)
+                        }),
+                    };
+                    task.project_id == project_id
+                        && in_section
+                        && task
+                            .parent_id
+                            .as_deref()
+                            .is_none_or(|parent| !ids.contains(parent))
+                })
+                .collect();
+            indices.sort_by_key(|index| tasks.tasks[*index].order);
+            indices
+        };
+
+        let projects = Projects::new(tasks.projects.clone());
+        for index in &projects.display_projects {
+            let project = &projects.projects[*index];
+            let mut path: Vec<&str> = tree::ancestors(&projects.projects, project)
+                .map(|parent| parent.name.as_str())
+                .collect();
+            path.reverse();
+            path.push(&project.name);
+            export.entries.push(Entry::Heading {
+                level: 2,
+                text: path.join(" / "),
+            });
+            for index in top_level(&project.id, None) {
+                export.push_task(tasks, index, 0);
+            }
+            for section in tasks
+                .sections
+                .iter()
+                .filter(|section| section.project_id == project.id)
+            {
+                export.entries.push(Entry::Heading {
+                    level: 3,
+                    text: section.name.clone(),
+                });
+                for index in top_level(&project.id, Some(&section.id)) {
+                    export.push_task(tasks, index, 0);
+                }
+            }
+        }
+        export
+    }
+
+    /// Pushes the task followed by its subtasks, however deep.
+    fn push_task(&mut self, tasks: &Tasks, index: usize, depth: usize) {
+        let task = &tasks.tasks[index];
+        self.entries.push(Entry::Task {
+            task: Box::new(task.clone()),
+            depth,
+        });
+        if !tree::in_cycle(&tasks.tasks, task) {
+            for child in tasks.children(&task.id) {
+                self.push_task(tasks, child, depth + 1);
+            }
+        }
+    }
+
+    pub fn task_count(&self) -> usize {
+        self.tasks().count()
+    }
+
+    fn tasks(&self) -> impl Iterator<Item = &Task> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Task { task, .. } => Some(task.as_ref()),
+            Entry::Heading { .. } => None,
+        })
+    }
+
+    pub fn write(&self, format: ExportFormat) -> Result<String, String> {
+        match format {
+            ExportFormat::Json => {
+                let tasks: Vec<&Task> = self.tasks().collect();
+                serde_json::to_string_pretty(&tasks).map_err(|e| e.to_string())
+            }
+            ExportFormat::Csv => Ok(self.csv()),
+            ExportFormat::Markdown => Ok(self.markdown()),
+        }
+    }
+
+    fn csv(&self) -> String {
+        let mut csv = String::from(
+            "id,parent_id,project,section,content,description,priority,due_date,due_string,is_recurring,labels,created_at\n",
+        );
+        for task in self.tasks() {
+            let due = task.due.as_ref();
+            let fields = [
+                task.id.clone(),
+                task.parent_id.clone().unwrap_or_default(),
+                self.project_names
+                    .get(&task.project_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                task.section_id
+                    .as_ref()
+                    .and_then(|id| self.section_names.get(id))
+                    .cloned()
+                    .unwrap_or_default(),
+                task.content.clone(),
+                task.description.clone(),
+                tasks::priority_name(task.priority).to_string(),
+                due.map_or(String::new(), |due| due.date.to_string()),
+                due.map_or(String::new(), |due| due.string.clone()),
+                due.is_some_and(|due| due.is_recurring).to_string(),
+                task.labels.join(","),
+                task.created_at.clone(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    fn markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.title);
+        // a list starts after a blank line
+        let mut list_started = false;
+        for entry in &self.entries {
+            match entry {
+                Entry::Heading { level, text } => {
+                    markdown.push_str(&format!("\n{} {}\n", "#".repeat(*level), text));
+                    list_started = false;
+                }
+                Entry::Task { task, depth } => {
+                    if !list_started {
+                        markdown.push('\n');
+                        list_started = true;
+                    }
+                    let indent = "  ".repeat(*depth);
+                    let mut line = format!(
+                        "{}- [{}] {}",
+                        indent,
+                        if task.is_completed { "x" } else { " " },
+                        task.content
+                    );
+                    if let Some(due) = &task.due {
+                        line.push_str(&format!(" (due {})", due.date));
+                    }
+                    if task.priority > 1 {
+                        line.push_str(&format!(" {}", tasks::priority_name(task.priority)));
+                    }
+                    for label in &task.labels {
+                        line.push_str(&format!(" @{}", label));
+                    }
+                    markdown.push_str(&line);
+                    markdown.push('\n');
+                    for description in task.description.lines().filter(|l| !l.is_empty()) {
+                        markdown.push_str(&format!("{}  {}\n", indent, description));
+                    }
+                }
+            }
+        }
+        markdown
+    }
+}
+
+/// Quotes the field when it has a comma, quote or line break in it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        import::{Import, ImportFormat},
+        projects::Project,
+        sections::Section,
+        tasks::Filter,
+    };
+
+    fn task(id: &str, json: serde_json::Value) -> Task {
+        let mut task = Task::from_json(id.to_string(), &json);
+        task.order = id.parse().unwrap_or_default();
+        task
+    }
+
+    fn tasks() -> Tasks {
+        let mut tasks = Tasks::new(vec![
+            task(
+                "1",
+                json!({
+                    "content": "Write report, final",
+                    "project_id": "clients",
+                    "section_id": "doing",
+                    "priority": 4,
+                    "labels": ["deep"],
+                    "due_date": "2024-05-01",
+                    "description": "Ask \"Sam\" first",
+                }),
+            ),
+            task(
+                "2",
+                json!({ "content": "Outline", "project_id": "clients", "parent_id": "1" }),
+            ),
+            task(
+                "3",
+                json!({ "content": "Sources", "project_id": "clients", "parent_id": "2" }),
+            ),
+            task(
+                "4",
+                json!({ "content": "Invoice", "project_id": "clients" }),
+            ),
+            task("5", json!({ "content": "Plan", "project_id": "work" })),
+        ]);
+        tasks.set_projects(&[
+            Project::from_json("work".to_string(), &json!({ "name": "Work" })),
+            Project::from_json(
+                "clients".to_string(),
+                &json!({ "name": "Clients", "parent_id": "work" }),
+            ),
+        ]);
+        tasks.set_sections(&[Section::from_json(
+            "doing".to_string(),
+            &json!({ "project_id": "clients", "name": "Doing" }),
+        )]);
+        tasks
+    }
+
+    #[test]
+    fn account_markdown() {
+        let markdown = Export::account(&tasks())
+            .write(ExportFormat::Markdown)
+            .unwrap();
+        assert_eq!(
+            markdown,
+            "\
+# All tasks
+
+## Work
+
+- [ ] Plan
+
+## Work / Clients
+
+- [ ] Invoice
+
+### Doing
+
+- [ ] Write report, final (due 2024-05-01) P1 @deep
+  Ask \"Sam\" first
+  - [ ] Outline
+    - [ ] Sources
+"
+        );
+    }
+
+    #[test]
+    fn csv_quotes_what_needs_it() {
+        let csv = Export::account(&tasks()).write(ExportFormat::Csv).unwrap();
+        let report = csv.lines().find(|line| line.starts_with("1,")).unwrap();
+        assert!(report.starts_with(
+            "1,,Clients,Doing,\"Write report, final\",\"Ask \"\"Sam\"\" first\",P1,2024-05-01,"
+        ));
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn markdown_reads_back() {
+        let markdown = Export::account(&tasks())
+            .write(ExportFormat::Markdown)
+            .unwrap();
+        let import = Import::parse(&markdown, ImportFormat::Markdown).unwrap();
+
+        let contents: Vec<&str> = import.drafts.iter().map(|d| d.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            [
+                "Plan",
+                "Invoice",
+                "Write report, final",
+                "Outline",
+                "Sources"
+            ]
+        );
+        let report = &import.drafts[2];
+        assert_eq!(report.section.as_deref(), Some("Doing"));
+        assert_eq!(report.priority, 4);
+        assert_eq!(report.labels, ["deep"]);
+        assert_eq!(report.due_string.as_deref(), Some("2024-05-01"));
+        assert_eq!(report.description, "Ask \"Sam\" first");
+        assert_eq!(import.drafts[4].parent, Some(3));
+        assert_eq!(import.drafts[3].parent, Some(2));
+    }
+
+    #[test]
+    fn csv_reads_back() {
+        let csv = Export::account(&tasks()).write(ExportFormat::Csv).unwrap();
+        let import = Import::parse(&csv, ImportFormat::Csv).unwrap();
+
+        let report = &import.drafts[2];
+        assert_eq!(report.content, "Write report, final");
+        assert_eq!(report.description, "Ask \"Sam\" first");
+        assert_eq!(report.section.as_deref(), Some("Doing"));
+        assert_eq!(report.priority, 4);
+        assert_eq!(report.labels, ["deep"]);
+        assert_eq!(import.drafts[3].parent, Some(2));
+        assert_eq!(import.drafts[4].parent, Some(3));
+    }
+
+    #[test]
+    fn a_view_takes_its_tasks_with_their_subtasks() {
+        let mut tasks = tasks();
+        tasks.filter = Filter::Query("p1".to_string());
+        let export = Export::view("Urgent", &tasks);
+        assert_eq!(export.task_count(), 3);
+
+        let json = export.write(ExportFormat::Json).unwrap();
+        let tasks: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        let ids: Vec<&str> = tasks.iter().map(|t| t["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["1", "2", "3"]);
+    }
+
+    #[test]
+    fn a_parent_cycle_ends() {
+        let tasks = Tasks::new(vec![
+            task(
+                "1",
+                json!({ "content": "A", "project_id": "p", "parent_id": "2" }),
+            ),
+            task(
+                "2",
+                json!({ "content": "B", "project_id": "p", "parent_id": "1" }),
+            ),
+        ]);
+        let mut export = Export::new("Cycle", &tasks);
+        export.push_task(&tasks, 0, 0);
+        assert_eq!(export.task_count(), 1);
+    }
+}
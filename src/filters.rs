@@ -0,0 +1,189 @@
+use ratatui::widgets::ListState;
+use serde::{Deserialize, Serialize};
+
+use crate::query::Query;
+
+/// Keys the interface already does something with, a filter can't have them.
+const RESERVED_KEYS: &str = "hqQEIucrRpdjklxyesmnACJKNS/+-<>[]{}";
+
+/// A named filter query, from the config or the account.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SavedFilter {
+    /// Only filters synced from the account have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    pub query: String,
+    /// Key that shows the filter from anywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<char>,
+    #[serde(default)]
+    pub order: i32,
+}
+
+impl SavedFilter {
+    fn new(name: &str, query: &str, key: char) -> SavedFilter {
+        SavedFilter {
+            id: None,
+            name: name.to_string(),
+            query: query.to_string(),
+            key: Some(key),
+            order: 0,
+        }
+    }
+
+    /// What there is when the config doesn't list any filters.
+    pub fn defaults() -> Vec<SavedFilter> {
+        vec![
+            SavedFilter::new("Today", "today", 't'),
+            SavedFilter::new("Overdue", "overdue", 'o'),
+            SavedFilter::new("All", "all", 'a'),
+        ]
+    }
+
+    /// Checks filters from the config: each key used once and by nothing
+    /// else, and queries that parse.
+    pub fn validate(filters: &[SavedFilter]) -> Result<(), String> {
+        for (i, filter) in filters.iter().enumerate() {
+            if let Some(key) = filter.key {
+                if RESERVED_KEYS.contains(key) {
+                    return Err(format!(
+                        "the key {} of \"{}\" is already used for something else",
+                        key, filter.name
+                    ));
+                }
+                if let Some(other) = filters[..i].iter().find(|other| other.key == Some(key)) {
+                    return Err(format!(
+                        "the key {} is given to both \"{}\" and \"{}\"",
+                        key, other.name, filter.name
+                    ));
+                }
+            }
+            Query::parse(&filter.query)
+                .map_err(|e| format!("the query of \"{}\" is invalid: {}", filter.name, e))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Filters {
+    /// The configured filters followed by the account's. `state` indexes
+    /// into this.
+    pub filters: Vec<SavedFilter>,
+    pub state: ListState,
+    configured: Vec<SavedFilter>,
+    account: Vec<SavedFilter>,
+}
+
+impl Filters {
+    pub fn new(configured: Vec<SavedFilter>) -> Filters {
+        Filters {
+            filters: configured.clone(),
+            state: ListState::default(),
+            configured,
+            account: Vec::new(),
+        }
+    }
+
+    pub fn account(&self) -> &[SavedFilter] {
+        &self.account
+    }
+
+    /// Replaces the filters synced from the account, keeping the
+    /// highlighted filter.
+    pub fn set_account(&mut self, mut account: Vec<SavedFilter>) {
+        let highlighted = self.selected().cloned();
+        account.sort_by_key(|filter| filter.order);
+        self.filters = self.configured.iter().chain(&account).cloned().collect();
+        self.account = account;
+        let i = highlighted.and_then(|highlighted| {
+            self.filters
+                .iter()
+                .position(|filter| filter.name == highlighted.name)
+        });
+        self.state.select(i);
+    }
+
+    pub fn next(&mut self) {
+        if self.filters.is_empty() {
+            self.state.select(None);
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i + 1 < self.filters.len() => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        if self.filters.is_empty() {
+            self.state.select(None);
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(0) | None => self.filters.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn unselect(&mut self) {
+        let offset = self.state.offset();
+        self.state.select(None);
+        *self.state.offset_mut() = offset;
+    }
+
+    pub fn selected(&self) -> Option<&SavedFilter> {
+        self.state.selected().and_then(|i| self.filters.get(i))
+    }
+
+    /// Index of the filter bound to `key`.
+    pub fn position_of_key(&self, key: char) -> Option<usize> {
+        self.filters
+            .iter()
+            .position(|filter| filter.key == Some(key))
+    }
+
+    /// Name to show for a query, when a filter has it.
+    pub fn name_of(&self, query: &str) -> Option<&str> {
+        self.selected()
+            .filter(|filter| filter.query == query)
+            .or_else(|| self.filters.iter().find(|filter| filter.query == query))
+            .map(|filter| filter.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_defaults_are_valid() {
+        assert_eq!(SavedFilter::validate(&SavedFilter::defaults()), Ok(()));
+    }
+
+    #[test]
+    fn rejects_keys_the_interface_uses() {
+        for key in ['x', 'Q', '/', 'd'] {
+            let filters = [SavedFilter::new("Work", "#Work", key)];
+            assert!(SavedFilter::validate(&filters).is_err(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn rejects_a_key_given_twice() {
+        let filters = [
+            SavedFilter::new("Work", "#Work", 'w'),
+            SavedFilter::new("Waiting", "@waiting", 'w'),
+        ];
+        assert!(SavedFilter::validate(&filters).is_err());
+    }
+
+    #[test]
+    fn rejects_an_invalid_query() {
+        let filters = [SavedFilter::new("Urgent", "p1 &", 'g')];
+        assert!(SavedFilter::validate(&filters).is_err());
+    }
+}
@@ -0,0 +1,716 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::json;
+
+use crate::{quick_add, tasks};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    /// `- [ ]` items, nested ones being subtasks, under `##` section headers.
+    Markdown,
+    /// Todoist's template format, or what `export` writes.
+    Csv,
+    /// A task a line, see todotxt.org.
+    TodoTxt,
+}
+
+impl ImportFormat {
+    pub fn parse(name: &str) -> Option<ImportFormat> {
+        match name.to_lowercase().as_str() {
+            "md" | "markdown" => Some(ImportFormat::Markdown),
+            "csv" => Some(ImportFormat::Csv),
+            "txt" | "todotxt" | "todo.txt" => Some(ImportFormat::TodoTxt),
+            _ => None,
+        }
+    }
+
+    /// Going by the file's extension.
+    pub fn from_path(path: &Path) -> Option<ImportFormat> {
+        ImportFormat::parse(path.extension()?.to_str()?)
+    }
+}
+
+/// A task read from a file, not created yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Draft {
+    pub content: String,
+    pub description: String,
+    /// As the API counts it, 4 being P1.
+    pub priority: u8,
+    pub due_string: Option<String>,
+    pub labels: Vec<String>,
+    /// Of the target project, created when it doesn't exist. Subtasks are
+    /// in their parent's.
+    pub section: Option<String>,
+    /// Index of the parent task among the drafts, always an earlier one.
+    pub parent: Option<usize>,
+}
+
+impl Draft {
+    /// Reads the priority, labels and due date at the end of the content,
+    /// the way `export` writes them: `Call Sam (due 2024-05-01) P1 @phone`.
+    fn new(text: &str, section: Option<String>, parent: Option<usize>) -> Draft {
+        let mut draft = Draft {
+            content: String::new(),
+            description: String::new(),
+            priority: 1,
+            due_string: None,
+            labels: Vec::new(),
+            section,
+            parent,
+        };
+        let mut content = text.trim();
+        loop {
+            if let Some(rest) = content.strip_suffix(')') {
+                if let Some(start) = rest.rfind("(due ") {
+                    draft.due_string = Some(rest[start + 5..].trim().to_string());
+                    content = rest[..start].trim_end();
+                    continue;
+                }
+            }
+            let (rest, word) = content.rsplit_once(' ').unwrap_or(("", content));
+            if let Some(label) = word.strip_prefix('@').filter(|label| !label.is_empty()) {
+                draft.labels.insert(0, label.to_string());
+            } else if let Some(priority) = quick_add::parse_priority(word) {
+                draft.priority = priority;
+            } else {
+                break;
+            }
+            content = rest.trim_end();
+        }
+        // a task that is nothing but labels keeps them as its name
+        draft.content = if content.is_empty() {
+            text.trim()
+        } else {
+            content
+        }
+        .to_string();
+        draft
+    }
+
+    /// The content followed by its due date, priority and labels.
+    pub fn summary(&self) -> String {
+        let mut summary = self.content.clone();
+        if let Some(due_string) = &self.due_string {
+            summary.push_str(&format!(" (due {})", due_string));
+        }
+        if self.priority > 1 {
+            summary.push_str(&format!(" {}", tasks::priority_name(self.priority)));
+        }
+        for label in &self.labels {
+            summary.push_str(&format!(" @{}", label));
+        }
+        summary
+    }
+
+    /// What `create_task` needs, given the ids of the project, section and
+    /// parent the draft goes in.
+    pub fn to_json(
+        &self,
+        project_id: &str,
+        section_id: Option<&str>,
+        parent_id: Option<&str>,
+    ) -> serde_json::Value {
+        let mut json = json!({
+            "content": self.content,
+            "project_id": project_id,
+            "priority": self.priority,
+        });
+        if !self.description.is_empty() {
+            json["description"] = json!(self.description);
+        }
+        if !self.labels.is_empty() {
+            json["labels"] = json!(self.labels);
+        }
+        if let Some(due_string) = &self.due_string {
+            json["due_string"] = json!(due_string);
+        }
+        if let Some(section_id) = section_id {
+            json["section_id"] = json!(section_id);
+        }
+        if let Some(parent_id) = parent_id {
+            json["parent_id"] = json!(parent_id);
+        }
+        json
+    }
+}
+
+/// The tasks of a file, parents before their subtasks.
+#[derive(Debug, Clone, Default)]
+pub struct Import {
+    pub drafts: Vec<Draft>,
+    /// Completed tasks, and their subtasks, which are left out.
+    pub skipped: usize,
+}
+
+impl Import {
+    pub fn read(path: &Path, format: ImportFormat) -> Result<Import, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let import = Import::parse(&text, format)?;
+        if import.drafts.is_empty() {
+            return Err(format!("Found no tasks to import in {}", path.display()));
+        }
+        Ok(import)
+    }
+
+    pub fn parse(text: &str, format: ImportFormat) -> Result<Import, String> {
+        let text = text.trim_start_matches('\u{feff}');
+        match format {
+            ImportFormat::Markdown => Ok(Import::markdown(text)),
+            ImportFormat::Csv => Import::csv(text),
+            ImportFormat::TodoTxt => Ok(Import::todo_txt(text)),
+        }
+    }
+
+    fn markdown(text: &str) -> Import {
+        let mut import = Import::default();
+        let mut section = None;
+        // open items by indentation, `None` for a completed one
+        let mut open: Vec<(usize, Option<usize>)> = Vec::new();
+        let mut in_code = false;
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_code = !in_code;
+                continue;
+            }
+            if in_code || trimmed.is_empty() {
+                continue;
+            }
+            let indent = line
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum();
+
+            if let Some((level, heading)) = markdown_heading(trimmed) {
+                // the first level is the document's title
+                if level > 1 {
+                    section = Some(heading.to_string());
+                }
+                open.clear();
+            } else if let Some((done, item)) = checklist_item(trimmed) {
+                while open.last().is_some_and(|(open, _)| *open >= indent) {
+                    open.pop();
+                }
+                let index = match open.last() {
+                    // under a completed task
+                    Some((_, None)) => None,
+                    _ if done => None,
+                    parent => Some(import.push(item, section.clone(), parent.and_then(|p| p.1))),
+                };
+                if index.is_none() {
+                    import.skipped += 1;
+                }
+                open.push((indent, index));
+            } else if let Some((_, last)) = open.last().filter(|(open, _)| indent > *open) {
+                // anything indented under a task describes it
+                if let Some(draft) = last.map(|index| &mut import.drafts[index]) {
+                    if !draft.description.is_empty() {
+                        draft.description.push('\n');
+                    }
+                    draft.description.push_str(trimmed);
+                }
+            } else {
+                open.clear();
+            }
+        }
+        import
+    }
+
+    /// Todoist's template columns, `TYPE`, `CONTENT`, `DESCRIPTION`,
+    /// `PRIORITY`, `INDENT` and `DATE`, or the `id`, `parent_id`, `section`,
+    /// `due_string` and `labels` ones of an export.
+    fn csv(text: &str) -> Result<Import, String> {
+        let mut records = csv_records(text).into_iter();
+        let header: Vec<String> = records
+            .next()
+            .unwrap_or_default()
+            .iter()
+            .map(|name| name.trim().to_lowercase())
+            .collect();
+        let column = |names: &[&str]| {
+            header
+                .iter()
+                .position(|name| names.contains(&name.as_str()))
+        };
+        let content_column =
+            column(&["content"]).ok_or("The CSV file has no CONTENT column".to_string())?;
+        let type_column = column(&["type"]);
+        let description_column = column(&["description"]);
+        let priority_column = column(&["priority"]);
+        let indent_column = column(&["indent"]);
+        let due_columns = [
+            column(&["date"]),
+            column(&["due_string"]),
+            column(&["due_date"]),
+        ];
+        let labels_column = column(&["labels"]);
+        let section_column = column(&["section"]);
+        let id_column = column(&["id"]);
+        let parent_column = column(&["parent_id"]);
+
+        let mut import = Import::default();
+        let mut section = None;
+        let mut open: Vec<(usize, Option<usize>)> = Vec::new();
+        // drafts by the id the export gave them
+        let mut ids = HashMap::new();
+        for record in records {
+            let field = |column: Option<usize>| {
+                column
+                    .and_then(|column| record.get(column))
+                    .map_or("", |field| field.trim())
+            };
+            let content = field(Some(content_column));
+            match field(type_column).to_lowercase().as_str() {
+                "section" => {
+                    section = Some(content.to_string()).filter(|name| !name.is_empty());
+                    open.clear();
+                    continue;
+                }
+                "" | "task" => {}
+                // notes are comments, not tasks
+                _ => continue,
+            }
+            if content.is_empty() {
+                continue;
+            }
+
+            let depth = field(indent_column)
+                .parse::<usize>()
+                .map_or(0, |indent| indent.saturating_sub(1));
+            while open.last().is_some_and(|(open, _)| *open >= depth) {
+                open.pop();
+            }
+            let parent = match ids.get(field(parent_column)) {
+                Some(parent) => Some(*parent),
+                None => open.last().and_then(|(_, parent)| *parent),
+            };
+            let section = match field(section_column) {
+                "" => section.clone(),
+                name => Some(name.to_string()),
+            };
+            let index = import.push(content, section, parent);
+            open.push((depth, Some(index)));
+            if !field(id_column).is_empty() {
+                ids.insert(field(id_column).to_string(), index);
+            }
+
+            let draft = &mut import.drafts[index];
+            draft.description = field(description_column).to_string();
+            // counted like in the interface, P1 is 1
+            let priority = field(priority_column);
+            if let Some(priority) = quick_add::parse_priority(priority)
+                .or_else(|| quick_add::parse_priority(&format!("p{}", priority)))
+            {
+                draft.priority = priority;
+            }
+            if let Some(due) = due_columns
+                .iter()
+                .map(|column| field(*column))
+                .find(|due| !due.is_empty())
+            {
+                draft.due_string = Some(due.to_string());
+            }
+            for label in field(labels_column).split(',').map(str::trim) {
+                if !label.is_empty() && !draft.labels.iter().any(|l| l == label) {
+                    draft.labels.push(label.to_string());
+                }
+            }
+        }
+        Ok(import)
+    }
+
+    /// Priorities `(A)` to `(C)` are P1 to P3, `+project` is the section,
+    /// `@context` a label and `due:` the due date.
+    fn todo_txt(text: &str) -> Import {
+        let mut import = Import::default();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line.starts_with("x ") {
+                import.skipped += 1;
+                continue;
+            }
+            let mut words = line.split_whitespace().peekable();
+            let mut priority = 1;
+            if let Some(letter) = words
+                .peek()
+                .and_then(|word| word.strip_prefix('(')?.strip_suffix(')'))
+            {
+                priority = match letter {
+                    "A" => 4,
+                    "B" => 3,
+                    "C" => 2,
+                    _ => 1,
+                };
+                words.next();
+            }
+            // the creation date
+            while words
+                .peek()
+                .is_some_and(|word| chrono::NaiveDate::parse_from_str(word, "%Y-%m-%d").is_ok())
+            {
+                words.next();
+            }
+
+            let mut content = Vec::new();
+            let mut section = None;
+            let mut labels = Vec::new();
+            let mut due_string = None;
+            for word in words {
+                if let Some(label) = word.strip_prefix('@').filter(|l| !l.is_empty()) {
+                    labels.push(label.to_string());
+                } else if let Some(project) = word
+                    .strip_prefix('+')
+                    .filter(|p| !p.is_empty() && section.is_none())
+                {
+                    section = Some(project.to_string());
+                } else if let Some(due) = word.strip_prefix("due:").filter(|d| !d.is_empty()) {
+                    due_string = Some(due.to_string());
+                } else {
+                    content.push(word);
+                }
+            }
+            if content.is_empty() {
+                continue;
+            }
+            let index = import.push(&content.join(" "), section, None);
+            let draft = &mut import.drafts[index];
+            draft.priority = priority;
+            draft.labels.extend(labels);
+            draft.due_string = due_string.or(draft.due_string.take());
+        }
+        import
+    }
+
+    fn push(&mut self, text: &str, section: Option<String>, parent: Option<usize>) -> usize {
+        let section = match parent {
+            Some(parent) => self.drafts[parent].section.clone(),
+            None => section,
+        };
+        self.drafts.push(Draft::new(text, section, parent));
+        self.drafts.len() - 1
+    }
+
+    /// How many parents the draft has.
+    pub fn depth(&self, index: usize) -> usize {
+        let mut depth = 0;
+        let mut parent = self.drafts[index].parent;
+        while let Some(index) = parent {
+            depth += 1;
+            parent = self.drafts[index].parent;
+        }
+        depth
+    }
+
+    /// Whether the draft is the first of its section, or the first outside
+    /// any after some that are in one.
+    pub fn starts_section(&self, index: usize) -> bool {
+        match index {
+            0 => self.drafts[0].section.is_some(),
+            _ => self.drafts[index].section != self.drafts[index - 1].section,
+        }
+    }
+
+    /// The sections the drafts go in, in the order they first appear.
+    pub fn section_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for name in self
+            .drafts
+            .iter()
+            .filter_map(|draft| draft.section.as_deref())
+        {
+            if !names.iter().any(|known| known.eq_ignore_ascii_case(name)) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// How many tasks go to the project and how many were left out, e.g.
+    /// "3 tasks to Work, leaving out 1 completed".
+    pub fn describe(&self, project: &str) -> String {
+        let tasks = match self.drafts.len() {
+            1 => "1 task".to_string(),
+            n => format!("{} tasks", n),
+        };
+        match self.skipped {
+            0 => format!("{} to {}", tasks, project),
+            n => format!("{} to {}, leaving out {} completed", tasks, project, n),
+        }
+    }
+}
+
+/// A file's tasks waiting for a yes in the interface.
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub path: PathBuf,
+    pub import: Import,
+    /// Where the tasks go, Tab picks another.
+    pub project_id: String,
+    pub scroll: u16,
+}
+
+/// The level and text of a `## Heading`.
+fn markdown_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?.trim();
+    (level > 0 && !text.is_empty()).then_some((level, text))
+}
+
+/// Whether a `- [ ]`, `* [x]` or `1. [ ]` item is done, and its text.
+fn checklist_item(line: &str) -> Option<(bool, &str)> {
+    let rest = match line.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let digits = line.chars().take_while(char::is_ascii_digit).count();
+            line[digits..]
+                .strip_prefix(['.', ')'])
+                .filter(|_| digits > 0)?
+        }
+    };
+    let rest = rest.strip_prefix(' ')?.trim_start();
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let text = rest[3..].trim();
+    (!text.is_empty() && (rest.len() == 3 || rest[3..].starts_with(' '))).then_some((done, text))
+}
+
+/// The records of a CSV file, with quoted fields holding commas, quotes and
+/// line breaks.
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|field| !field.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    record.push(field);
+    if record.iter().any(|field| !field.is_empty()) {
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(import: &Import) -> Vec<&str> {
+        import
+            .drafts
+            .iter()
+            .map(|draft| draft.content.as_str())
+            .collect()
+    }
+
+    fn parents(import: &Import) -> Vec<Option<usize>> {
+        import.drafts.iter().map(|draft| draft.parent).collect()
+    }
+
+    #[test]
+    fn markdown_nesting_follows_indentation() {
+        let text = "\
+# Trip
+
+- [ ] Pack
+  - [ ] Clothes
+    - [ ] Socks
+  - [ ] Charger
+\t- [ ] Passport
+- [ ] Book hotel
+";
+        let import = Import::parse(text, ImportFormat::Markdown).unwrap();
+        assert_eq!(
+            contents(&import),
+            [
+                "Pack",
+                "Clothes",
+                "Socks",
+                "Charger",
+                "Passport",
+                "Book hotel"
+            ]
+        );
+        // a tab counts as four spaces, deeper than the Charger item
+        assert_eq!(
+            parents(&import),
+            [None, Some(0), Some(1), Some(0), Some(3), None]
+        );
+        assert_eq!(import.depth(2), 2);
+    }
+
+    #[test]
+    fn markdown_sections_descriptions_and_completed_items() {
+        let text = "\
+# Title, not a section
+
+- [ ] Loose task
+  with a description
+  over two lines
+
+## Errands
+1. [ ] Buy milk (due tomorrow) P1 @shop
+2. [x] Post letter
+   - [ ] Stamp
+* [ ] Return books
+
+```
+- [ ] in a code block
+```
+";
+        let import = Import::parse(text, ImportFormat::Markdown).unwrap();
+        assert_eq!(
+            contents(&import),
+            ["Loose task", "Buy milk", "Return books"]
+        );
+        assert_eq!(import.skipped, 2);
+        assert_eq!(
+            import.drafts[0].description,
+            "with a description\nover two lines"
+        );
+        assert_eq!(import.drafts[0].section, None);
+        assert_eq!(import.section_names(), ["Errands"]);
+        assert!(import.starts_section(1));
+        assert!(!import.starts_section(2));
+
+        let milk = &import.drafts[1];
+        assert_eq!(milk.due_string.as_deref(), Some("tomorrow"));
+        assert_eq!(milk.priority, 4);
+        assert_eq!(milk.labels, ["shop"]);
+        assert_eq!(milk.summary(), "Buy milk (due tomorrow) P1 @shop");
+    }
+
+    #[test]
+    fn not_checklist_items() {
+        for line in [
+            "- [ ]",
+            "-[ ] No space",
+            "- [?] Unknown",
+            "- [ ]x",
+            "1 [ ] No dot",
+        ] {
+            assert_eq!(checklist_item(line), None, "{:?}", line);
+        }
+        assert_eq!(checklist_item("+ [X] Done"), Some((true, "Done")));
+        assert_eq!(checklist_item("12) [ ] Twelfth"), Some((false, "Twelfth")));
+    }
+
+    #[test]
+    fn csv_quoted_fields() {
+        let text = "a,b,c\r\n\"one, two\",\"say \"\"hi\"\"\",\"line\nbreak\"\n,,\nlast,,\n";
+        assert_eq!(
+            csv_records(text),
+            [
+                vec!["a", "b", "c"],
+                vec!["one, two", "say \"hi\"", "line\nbreak"],
+                vec!["last", "", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_in_todoists_template_format() {
+        let text = "\
+\u{feff}TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE
+section,Errands,,,,,,
+task,\"Buy milk, eggs\",From the farm,1,1,,,tomorrow
+task,Eggs,,4,2,,,
+note,A comment,,,,,,
+task,Bake,,2,1,,,
+";
+        let import = Import::parse(text, ImportFormat::Csv).unwrap();
+        assert_eq!(contents(&import), ["Buy milk, eggs", "Eggs", "Bake"]);
+        assert_eq!(parents(&import), [None, Some(0), None]);
+        assert_eq!(import.drafts[0].description, "From the farm");
+        assert_eq!(import.drafts[0].priority, 4);
+        assert_eq!(import.drafts[0].due_string.as_deref(), Some("tomorrow"));
+        assert_eq!(import.drafts[1].priority, 1);
+        assert_eq!(import.drafts[2].priority, 3);
+        assert!(import
+            .drafts
+            .iter()
+            .all(|draft| draft.section.as_deref() == Some("Errands")));
+    }
+
+    #[test]
+    fn csv_without_content_is_an_error() {
+        assert!(Import::parse("name,date\nx,today\n", ImportFormat::Csv).is_err());
+    }
+
+    #[test]
+    fn todo_txt_prefixes_and_tags() {
+        let text = "\
+(A) 2024-05-01 Call Sam +Work @phone due:2024-05-03
+x 2024-05-02 2024-05-01 Done already
+2024-04-30 Water plants @home @garden
+(B) Plan trip
+(Z) Someday
++Work @phone
+";
+        let import = Import::parse(text, ImportFormat::TodoTxt).unwrap();
+        assert_eq!(
+            contents(&import),
+            ["Call Sam", "Water plants", "Plan trip", "Someday"]
+        );
+        assert_eq!(import.skipped, 1);
+
+        let call = &import.drafts[0];
+        assert_eq!(call.priority, 4);
+        assert_eq!(call.section.as_deref(), Some("Work"));
+        assert_eq!(call.labels, ["phone"]);
+        assert_eq!(call.due_string.as_deref(), Some("2024-05-03"));
+
+        assert_eq!(import.drafts[1].labels, ["home", "garden"]);
+        assert_eq!(import.drafts[1].priority, 1);
+        assert_eq!(import.drafts[2].priority, 3);
+        assert_eq!(import.drafts[3].priority, 1);
+    }
+
+    #[test]
+    fn to_json_only_sets_what_there_is() {
+        let import = Import::parse("- [ ] Plain", ImportFormat::Markdown).unwrap();
+        let json = import.drafts[0].to_json("p", None, Some("t"));
+        assert_eq!(json["content"], "Plain");
+        assert_eq!(json["project_id"], "p");
+        assert_eq!(json["parent_id"], "t");
+        assert!(json.get("section_id").is_none());
+        assert!(json.get("labels").is_none());
+        assert!(json.get("due_string").is_none());
+    }
+
+    #[test]
+    fn describes_what_goes_where() {
+        let import = Import::parse("- [ ] a\n- [x] b", ImportFormat::Markdown).unwrap();
+        assert_eq!(
+            import.describe("Work"),
+            "1 task to Work, leaving out 1 completed"
+        );
+    }
+}
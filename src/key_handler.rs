@@ -1,26 +1,48 @@
-use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::widgets::ListState;
-use reqwest::Client;
-
 use crate::{
-    api_calls::{self, close_task, create_task, delete_task},
-    new_task, task_edit, App, TaskResult,
+    comments::{Comment, CommentTarget, Comments},
+    confirm::{Action, Confirmation},
+    export::{self, Export, ExportFormat},
+    import::{Import, ImportFormat, Preview},
+    labels, new_task,
+    outbox::{self, Mutation},
+    project_edit::{self, ProjectEdit},
+    projects::{ListType, Project, Projects},
+    prompt::{Prompt, PromptAction},
+    query::Query,
+    sections::Section,
+    task_edit,
+    tasks::{self, Due, Filter, Task, TaskRow},
+    tui, App,
+};
+use chrono::Local;
+use crossterm::event::{KeyCode, KeyEvent};
+use serde_json::json;
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fs,
 };
+use tui_textarea::CursorMove;
 
-pub fn handle_task_editor(
-    app: &mut App,
-    key: KeyEvent,
-    client: Client,
-    tx: std::sync::mpsc::Sender<TaskResult>,
-) {
+pub fn handle_task_editor(app: &mut App, key: KeyEvent) {
     if key.code == KeyCode::Esc {
         app.show_task_editor = !app.show_task_editor;
-    } else if key.code == KeyCode::Enter {
+    } else if key.code == KeyCode::Enter
+        && app.task_edit.currently_editing != task_edit::CurrentlyEditing::Comments
+    {
+        // from the subtasks it is saved before the subtask is opened, the
+        // comments keep Enter to themselves
         app.show_task_editor = !app.show_task_editor;
-        let index = app.task_edit.current_task_index;
+        let Some(index) = app.tasks.index_of(&app.task_edit.task_id) else {
+            app.set_error_message("The task was completed or deleted in the meantime".into());
+            return;
+        };
 
         app.tasks.tasks[index].content = app.task_edit.content.lines().join("\n");
         app.tasks.tasks[index].description = app.task_edit.description.lines().join("\n");
+        app.tasks.tasks[index].labels =
+            labels::parse_labels(&app.task_edit.labels.lines().join(","));
+        app.tasks.tasks[index].priority = app.task_edit.priority;
+        app.tasks.filter_task_list();
 
         let task = app.tasks.tasks[index].clone();
 
@@ -29,8 +51,9 @@ pub fn handle_task_editor(
 
         json["due_string"] = serde_json::Value::String(app.task_edit.due_string.lines().join("\n"));
 
-        tokio::spawn(async move {
-            let _ = api_calls::update_task(&client, json, task.id.to_string(), tx).await;
+        app.outbox.push(Mutation::Update {
+            task: Box::new(task),
+            json,
         });
     }
     if key.code == KeyCode::Tab {
@@ -39,8 +62,14 @@ pub fn handle_task_editor(
         } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::Description {
             app.task_edit.currently_editing = task_edit::CurrentlyEditing::DueString
         } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::DueString {
+            app.task_edit.currently_editing = task_edit::CurrentlyEditing::Labels
+        } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::Labels {
+            app.task_edit.currently_editing = task_edit::CurrentlyEditing::Priority
+        } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::Priority {
             app.task_edit.currently_editing = task_edit::CurrentlyEditing::ChildTasks
         } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::ChildTasks {
+            app.task_edit.currently_editing = task_edit::CurrentlyEditing::Comments
+        } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::Comments {
             app.task_edit.currently_editing = task_edit::CurrentlyEditing::Content
         }
         app.task_edit.update_cursor_styles();
@@ -53,36 +82,32 @@ pub fn handle_task_editor(
         app.task_edit.description.input(key);
     } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::DueString {
         app.task_edit.due_string.input(key);
+    } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::Labels {
+        app.task_edit.labels.input(key);
+    } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::Priority {
+        app.task_edit.priority = pick_priority(app.task_edit.priority, key);
+    } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::Comments {
+        handle_comments(app, key);
     } else if app.task_edit.currently_editing == task_edit::CurrentlyEditing::ChildTasks {
         if key.code == KeyCode::Char('j') || key.code == KeyCode::Down {
             app.task_edit.next();
         } else if key.code == KeyCode::Char('k') || key.code == KeyCode::Up {
             app.task_edit.previous();
         } else if key.code == KeyCode::Enter {
-            if let Some(selected) = app.task_edit.children_list_state.selected() {
-                app.show_task_editor = true;
-                let index = app.task_edit.children[selected];
-                let selected = &app.tasks.tasks[index];
-
-                let mut children = Vec::new();
-
-                for (index, task) in app.tasks.tasks.iter().enumerate() {
-                    if task.parent_id == Some(selected.id.clone()) {
-                        children.push(index);
-                    }
-                }
-
-                app.task_edit = task_edit::TaskEdit::new(
-                    selected.content.clone(),
-                    selected.description.clone(),
-                    selected.due.as_ref().map_or("", |d| &d.string).to_string(),
-                    children,
-                    index,
-                    task_edit::CurrentlyEditing::Content,
-                );
+            if let Some(index) = app
+                .task_edit
+                .children_list_state
+                .selected()
+                .and_then(|selected| app.task_edit.children.get(selected))
+                .and_then(|id| app.tasks.index_of(id))
+            {
+                open_task_editor(app, index);
             }
         } else if key.code == KeyCode::Char('n') {
-            let task = app.tasks.tasks[app.task_edit.current_task_index].clone();
+            let Some(index) = app.tasks.index_of(&app.task_edit.task_id) else {
+                return;
+            };
+            let task = app.tasks.tasks[index].clone();
 
             app.show_task_editor = false;
             app.show_new_task = true;
@@ -92,59 +117,968 @@ pub fn handle_task_editor(
     }
 }
 
+/// Opens the task editor on `tasks[index]` along with its comments.
+fn open_task_editor(app: &mut App, index: usize) {
+    let task = &app.tasks.tasks[index];
+    let children = app
+        .tasks
+        .children(&task.id)
+        .into_iter()
+        .map(|child| app.tasks.tasks[child].id.clone())
+        .collect();
+    app.task_edit = task_edit::TaskEdit::new(task, children, task_edit::CurrentlyEditing::Content);
+    app.show_task_editor = true;
+    let target = CommentTarget::Task(task.id.clone());
+    show_comments(app, target);
+}
+
+/// The priority after a key press in a priority selector, which lists P1
+/// first.
+fn pick_priority(priority: u8, key: KeyEvent) -> u8 {
+    match key.code {
+        KeyCode::Left => tasks::bump_priority(priority, true),
+        KeyCode::Right | KeyCode::Char(' ') | KeyCode::Char('l') => {
+            tasks::bump_priority(priority, false)
+        }
+        KeyCode::Char(c @ '1'..='4') => 5 - c.to_digit(10).unwrap_or(4) as u8,
+        _ => priority,
+    }
+}
+
+/// Makes the highlighted task more (`raise`) or less urgent.
+fn bump_task_priority(app: &mut App, raise: bool) {
+    let Some(index) = app.tasks.selected_index() else {
+        return;
+    };
+    let task = &mut app.tasks.tasks[index];
+    let priority = tasks::bump_priority(task.priority, raise);
+    if priority == task.priority {
+        return;
+    }
+    task.priority = priority;
+
+    let task = task.clone();
+    let id = task.id.clone();
+    app.outbox.push(Mutation::Update {
+        task: Box::new(task),
+        json: json!({ "priority": priority }),
+    });
+    app.tasks.filter_task_list();
+    app.tasks.select_id(Some(&id));
+}
+
+/// Shows the tasks of the highlighted project, and of its sub-projects when
+/// those are included.
+fn show_project_tasks(app: &mut App) {
+    if let Some(selected_id) = app.projects.selected_id() {
+        app.tasks.subproject_ids = if app.projects.include_subprojects {
+            app.projects.descendants(&selected_id)
+        } else {
+            HashSet::new()
+        };
+        app.tasks.board = app
+            .projects
+            .selected()
+            .is_some_and(|project| project.view_style == ListType::Board);
+        app.tasks.filter = Filter::ProjectId(selected_id.clone());
+        app.tasks.filter_task_list();
+        app.projects.selected_project = Some(selected_id);
+        app.labels.unselect();
+        app.filters.unselect();
+    }
+}
+
+/// Shows the tasks of a saved filter, `index` into `Filters::filters`.
+pub fn show_filter(app: &mut App, index: usize) {
+    app.filters.state.select(Some(index));
+    if let Some(filter) = app.filters.selected() {
+        app.tasks.board = false;
+        app.tasks.filter = Filter::Query(filter.query.clone());
+        app.tasks.filter_task_list();
+        app.projects.selected_project = None;
+        app.projects.unselect();
+        app.labels.unselect();
+    }
+}
+
+pub fn show_upcoming(app: &mut App) {
+    app.tasks.board = false;
+    app.tasks.filter = Filter::Upcoming {
+        days: app.upcoming_days,
+    };
+    app.tasks.filter_task_list();
+    app.projects.selected_project = None;
+    app.projects.unselect();
+    app.labels.unselect();
+    app.filters.unselect();
+}
+
+/// Shows the tasks due on the day highlighted in the calendar.
+fn show_date(app: &mut App) {
+    app.tasks.board = false;
+    app.tasks.filter = Filter::Date(app.calendar.selected);
+    app.tasks.filter_task_list();
+    app.projects.selected_project = None;
+    app.projects.unselect();
+    app.labels.unselect();
+    app.filters.unselect();
+}
+
+/// Moves the highlighted task to the day highlighted in the calendar,
+/// keeping the time of day of timed tasks.
+fn reschedule_task(app: &mut App) {
+    let Some(index) = app.tasks.selected_index() else {
+        return;
+    };
+    let date = app.calendar.selected;
+    let task = &mut app.tasks.tasks[index];
+    if task.due.as_ref().is_some_and(|due| due.is_recurring) {
+        app.set_error_message("Recurring tasks can't be moved to a single day".to_string());
+        return;
+    }
+
+    let day = date.format("%Y-%m-%d").to_string();
+    let datetime = task
+        .due
+        .as_ref()
+        .and_then(|due| due.datetime.as_deref())
+        .and_then(|datetime| datetime.get(10..))
+        .map(|time| format!("{}{}", day, time));
+    let json = match &datetime {
+        Some(datetime) => json!({ "due_datetime": datetime }),
+        None => json!({ "due_date": day }),
+    };
+    task.due = Due::from_json(&json);
+
+    let task = task.clone();
+    let id = task.id.clone();
+    app.outbox.push(Mutation::Update {
+        task: Box::new(task),
+        json,
+    });
+    app.tasks.filter_task_list();
+    app.tasks.select_id(Some(&id));
+}
+
+pub fn handle_calendar(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Right | KeyCode::Char('l') => app.calendar.move_days(1),
+        KeyCode::Left => app.calendar.move_days(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.calendar.move_days(7),
+        KeyCode::Up | KeyCode::Char('k') => app.calendar.move_days(-7),
+        KeyCode::Char(']') => app.calendar.move_months(true),
+        KeyCode::Char('[') => app.calendar.move_months(false),
+        KeyCode::Enter => show_date(app),
+        KeyCode::Char('m') => reschedule_task(app),
+        _ => {}
+    }
+}
+
+pub fn handle_filters(app: &mut App, key: KeyEvent) {
+    if key.code == KeyCode::Char('j') || key.code == KeyCode::Down {
+        app.filters.next();
+    } else if key.code == KeyCode::Char('k') || key.code == KeyCode::Up {
+        app.filters.previous();
+    } else {
+        return;
+    }
+    if let Some(index) = app.filters.state.selected() {
+        show_filter(app, index);
+    }
+}
+
+/// Opens the new task modal for a task in the inbox with the label.
+fn new_labelled_task(app: &mut App, label: String) {
+    let Some(inbox) = app
+        .projects
+        .projects
+        .iter()
+        .find(|project| project.is_inbox_project)
+    else {
+        return;
+    };
+    app.new_task = new_task::NewTask::new(inbox.id.clone(), None);
+    app.new_task.set_labels(&[label]);
+    app.show_new_task = true;
+}
+
+pub fn handle_labels(app: &mut App, key: KeyEvent) {
+    if key.code == KeyCode::Char('j') || key.code == KeyCode::Down {
+        app.labels.next();
+    } else if key.code == KeyCode::Char('k') || key.code == KeyCode::Up {
+        app.labels.previous();
+    } else if key.code == KeyCode::Char('+') || key.code == KeyCode::Char('n') {
+        if let Some(label) = app.labels.selected() {
+            let name = label.name.clone();
+            new_labelled_task(app, name);
+        }
+        return;
+    } else {
+        return;
+    }
+
+    if let Some(label) = app.labels.selected() {
+        app.tasks.board = false;
+        app.tasks.filter = Filter::Label(label.name.clone());
+        app.tasks.filter_task_list();
+        app.projects.selected_project = None;
+        app.projects.unselect();
+        app.filters.unselect();
+    }
+}
+
 pub fn handle_projects(app: &mut App, key: KeyEvent) {
     if key.code == KeyCode::Char('j') || key.code == KeyCode::Down {
         app.projects.next();
-        if let Some(selected) = app.projects.state.selected() {
-            let selected_id = app.projects.projects[selected].id.clone();
-            app.tasks.filter = crate::tasks::Filter::ProjectId(selected_id.clone());
-            app.tasks.filter_task_list();
-            app.projects.selected_project = Some(selected_id);
-        }
+        show_project_tasks(app);
     } else if key.code == KeyCode::Char('k') || key.code == KeyCode::Up {
         app.projects.previous();
-        if let Some(selected) = app.projects.state.selected() {
-            let selected_id = app.projects.projects[selected].id.clone();
-            app.tasks.filter = crate::tasks::Filter::ProjectId(selected_id.clone());
-            app.tasks.filter_task_list();
-            app.projects.selected_project = Some(selected_id);
+        show_project_tasks(app);
+    } else if key.code == KeyCode::Right || key.code == KeyCode::Char('l') {
+        app.projects.set_collapsed(false);
+    } else if key.code == KeyCode::Left {
+        app.projects.set_collapsed(true);
+    } else if key.code == KeyCode::Char('s') {
+        app.projects.include_subprojects = !app.projects.include_subprojects;
+        show_project_tasks(app);
+    } else if key.code == KeyCode::Char('C') {
+        if let Some(project) = app.projects.selected() {
+            show_comments(app, CommentTarget::Project(project.id.clone()));
+            app.show_comments = true;
         }
-    } else if key.code == KeyCode::Char('x') {
-        todo!("DELETE PROJECT");
+    } else if key.code == KeyCode::Char('N') {
+        app.project_edit = ProjectEdit::new(&app.projects, None, None);
+        app.show_project_editor = true;
+    } else if key.code == KeyCode::Char('e') {
+        if let Some(project) = app.projects.selected() {
+            app.project_edit = ProjectEdit::new(&app.projects, Some(project), None);
+            app.show_project_editor = true;
+        }
+    } else if key.code == KeyCode::Char('A') || key.code == KeyCode::Char('x') {
+        let Some(project) = app.projects.selected() else {
+            return;
+        };
+        if project.is_inbox_project {
+            app.set_error_message("The Inbox can't be archived or deleted.".to_string());
+            return;
+        }
+        let id = project.id.clone();
+        let sub_projects = match app.projects.descendants(&id).len() {
+            0 => String::new(),
+            1 => " and its sub-project".to_string(),
+            n => format!(" and its {} sub-projects", n),
+        };
+        app.confirmation = Some(if key.code == KeyCode::Char('A') {
+            Confirmation::new(
+                format!("Archive \"{}\"{}?", project.name, sub_projects),
+                Action::ArchiveProject(id),
+            )
+        } else {
+            Confirmation::new(
+                format!(
+                    "Delete \"{}\"{} with all {} tasks? This can't be undone.",
+                    project.name,
+                    sub_projects,
+                    if sub_projects.is_empty() {
+                        "its"
+                    } else {
+                        "their"
+                    }
+                ),
+                Action::DeleteProject(id),
+            )
+        });
     } else if key.code == KeyCode::Char('+') || key.code == KeyCode::Char('n') {
-        if let Some(selected) = app.projects.state.selected() {
-            let selected_id = app.projects.projects[selected].id.clone();
+        if let Some(selected_id) = app.projects.selected_id() {
             app.show_new_task = true;
             app.new_task = new_task::NewTask::new(selected_id, None);
+            app.new_task.set_sections(&app.sections.sections, None);
         }
     }
 }
 
-pub fn handle_new_tasks(
-    app: &mut App,
-    key: KeyEvent,
-    client: Client,
-    tx: std::sync::mpsc::Sender<TaskResult>,
-) {
+pub fn handle_project_editor(app: &mut App, key: KeyEvent) {
     if key.code == KeyCode::Esc {
-        app.show_new_task = !app.show_new_task;
+        app.show_project_editor = false;
     } else if key.code == KeyCode::Enter {
-        app.show_new_task = !app.show_new_task;
-        let json = app.new_task.get_json();
+        if app.project_edit.name.lines().join("").trim().is_empty() {
+            return;
+        }
+        app.show_project_editor = false;
+        save_project(app);
+    } else if key.code == KeyCode::Tab {
+        app.project_edit.next_field();
+    } else if app.project_edit.currently_editing == project_edit::CurrentlyEditing::Name {
+        app.project_edit.name.input(key);
+    } else if matches!(
+        key.code,
+        KeyCode::Right | KeyCode::Char(' ') | KeyCode::Char('l')
+    ) {
+        app.project_edit.cycle(true);
+    } else if key.code == KeyCode::Left {
+        app.project_edit.cycle(false);
+    }
+}
+
+fn save_project(app: &mut App) {
+    let json = app.project_edit.get_json();
+    let parent_id = app.project_edit.parent_id();
+
+    let Some(project_id) = app.project_edit.project_id.clone() else {
+        let mut project = Project::from_json(outbox::temp_id(), &json);
+        project.order = app
+            .projects
+            .projects
+            .iter()
+            .map(|p| p.order)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        app.projects.projects.push(project.clone());
+        app.projects.build_tree();
+        app.tasks.set_projects(&app.projects.projects);
+        app.outbox.push(Mutation::CreateProject {
+            project: Box::new(project),
+            json,
+        });
+        return;
+    };
+
+    let Some(project) = app
+        .projects
+        .projects
+        .iter_mut()
+        .find(|project| project.id == project_id)
+    else {
+        return;
+    };
+    let edited = Project::from_json(project_id.clone(), &json);
+    project.name = edited.name;
+    project.color = edited.color;
+    project.is_favorite = edited.is_favorite;
+    project.view_style = edited.view_style;
+    let moved = project.parent_id != parent_id;
+    project.parent_id = parent_id.clone();
+    let project = project.clone();
+
+    app.projects.build_tree();
+    app.tasks.set_projects(&app.projects.projects);
+    app.projects.select_id(Some(&project_id));
+    if matches!(&app.tasks.filter, Filter::ProjectId(id) if *id == project_id) {
+        app.tasks.board = project.view_style == ListType::Board;
+        app.tasks.filter_task_list();
+    }
+
+    app.outbox.push(Mutation::UpdateProject {
+        project: Box::new(project),
+        json,
+    });
+    if moved {
+        app.outbox.push(Mutation::MoveProject {
+            project_id,
+            parent_id,
+        });
+    }
+}
+
+/// Starts showing the comments of a task or project, they are fetched by
+/// the main loop.
+fn show_comments(app: &mut App, target: CommentTarget) {
+    app.comments = Comments::new(target.clone());
+    // the authors are looked up among who's in a shared project
+    let project_id = match &target {
+        CommentTarget::Task(id) => app
+            .tasks
+            .index_of(id)
+            .map(|index| app.tasks.tasks[index].project_id.clone()),
+        CommentTarget::Project(id) => Some(id.clone()),
+    };
+    let shared_project = project_id
+        .filter(|id| !app.collaborators.contains_key(id))
+        .filter(|id| {
+            app.projects
+                .projects
+                .iter()
+                .any(|project| project.id == *id && project.is_shared)
+        });
+    if let Some(project_id) = shared_project {
+        app.collaborators.insert(project_id.clone(), None);
+        app.fetch_collaborators = Some(project_id);
+    }
+    if outbox::is_temp_id(target.param().1) {
+        // Todoist doesn't know about it yet, only the outbox has comments
+        app.comments.fetch = false;
+        app.set_comments(&target, Vec::new());
+    }
+}
+
+/// The comments of the task being edited, or of a project.
+pub fn handle_comments(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => app.comments.next(),
+        KeyCode::Char('k') | KeyCode::Up => app.comments.previous(),
+        KeyCode::Char('n') | KeyCode::Char('+') => {
+            if let Some(target) = app.comments.target.clone() {
+                app.prompt = Some(Prompt::new(
+                    "New comment",
+                    "",
+                    PromptAction::NewComment { target },
+                ));
+            }
+        }
+        KeyCode::Char('e') => {
+            if let Some(comment) = app.comments.selected() {
+                app.prompt = Some(Prompt::new(
+                    "Edit comment",
+                    &comment.content,
+                    PromptAction::EditComment {
+                        comment_id: comment.id.clone(),
+                    },
+                ));
+            }
+        }
+        KeyCode::Char('x') | KeyCode::Char('d') => {
+            if let Some(comment) = app.comments.selected() {
+                let preview: String = comment.content.chars().take(40).collect();
+                app.confirmation = Some(Confirmation::new(
+                    format!("Delete the comment \"{}\"?", preview),
+                    Action::DeleteComment(comment.id.clone()),
+                ));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn add_comment(app: &mut App, target: CommentTarget, content: String) {
+    let (key, id) = target.param();
+    let json = json!({ key: id, "content": content });
+    let comment = Comment::from_json(outbox::temp_id(), &json);
+    if app.comments.target.as_ref() == Some(&target) {
+        app.comments.comments.push(comment.clone());
+        app.comments.select_id(Some(&comment.id));
+    }
+    app.outbox.push(Mutation::CreateComment { comment, json });
+}
+
+fn edit_comment(app: &mut App, comment_id: String, content: String) {
+    let Some(comment) = app
+        .comments
+        .comments
+        .iter_mut()
+        .find(|comment| comment.id == comment_id)
+    else {
+        return;
+    };
+    comment.content = content.clone();
+    let comment = comment.clone();
+    app.outbox.push(Mutation::UpdateComment {
+        comment,
+        json: json!({ "content": content }),
+    });
+}
+
+pub fn handle_confirmation(app: &mut App, key: KeyEvent) {
+    if key.code == KeyCode::Esc || key.code == KeyCode::Char('n') {
+        app.confirmation = None;
+    } else if key.code == KeyCode::Char('y') || key.code == KeyCode::Enter {
+        let Some(confirmation) = app.confirmation.take() else {
+            return;
+        };
+        match confirmation.action {
+            Action::ArchiveProject(project_id) => {
+                app.remove_project(&project_id);
+                app.outbox.push(Mutation::ArchiveProject { project_id });
+            }
+            Action::DeleteProject(project_id) => {
+                app.remove_project(&project_id);
+                app.outbox.push(Mutation::DeleteProject { project_id });
+            }
+            Action::DeleteSection(section_id) => {
+                app.update_sections(|sections, tasks| {
+                    sections.retain(|section| section.id != section_id);
+                    tasks.retain(|task| task.section_id.as_ref() != Some(&section_id));
+                });
+                app.outbox.push(Mutation::DeleteSection { section_id });
+            }
+            Action::DeleteComment(comment_id) => {
+                let Some(index) = app
+                    .comments
+                    .comments
+                    .iter()
+                    .position(|comment| comment.id == comment_id)
+                else {
+                    return;
+                };
+                let comment = app.comments.comments.remove(index);
+                if app.comments.selected().is_none() {
+                    app.comments.previous();
+                }
+                app.outbox.push(Mutation::DeleteComment { comment });
+            }
+        }
+    }
+}
+
+fn export_title(all: bool) -> &'static str {
+    if all {
+        "Export all tasks to, Tab for this view"
+    } else {
+        "Export this view to, Tab for all tasks"
+    }
+}
+
+/// Asks where to export the view to, a Markdown file in the home directory
+/// to begin with.
+pub fn show_export(app: &mut App) {
+    let path = format!("~/todoist-{}.md", Local::now().format("%Y-%m-%d"));
+    app.prompt = Some(Prompt::new(
+        export_title(false),
+        &path,
+        PromptAction::Export { all: false },
+    ));
+}
 
-        tokio::spawn(async move {
-            let result = create_task(&client, json, tx).await;
-            if let Err(e) = result {
-                eprintln!("Failed to create task: {}", e);
+fn export_tasks(app: &mut App, path: &str, all: bool) {
+    let path = export::expand_home(path);
+    let Some(format) = ExportFormat::from_path(&path) else {
+        app.set_error_message("End the file name in .json, .csv or .md to pick a format".into());
+        return;
+    };
+    let export = if all {
+        Export::account(&app.tasks)
+    } else {
+        Export::view(&tui::view_title(app), &app.tasks)
+    };
+    let written = export
+        .write(format)
+        .and_then(|contents| fs::write(&path, contents).map_err(|e| e.to_string()));
+    match written {
+        Ok(()) => {
+            app.notice = Some(format!(
+                "Exported {} tasks to {}",
+                export.task_count(),
+                path.display()
+            ))
+        }
+        Err(e) => app.set_error_message(format!("Could not export to {}: {}", path.display(), e)),
+    }
+}
+
+/// Asks for the file to import tasks from.
+pub fn show_import(app: &mut App) {
+    app.prompt = Some(Prompt::new(
+        "Import tasks from a .md, .csv or todo.txt file",
+        "~/",
+        PromptAction::Import,
+    ));
+}
+
+/// Reads the file and shows what importing it would add.
+fn preview_import(app: &mut App, path: &str) {
+    let path = export::expand_home(path);
+    let Some(format) = ImportFormat::from_path(&path) else {
+        app.set_error_message("End the file name in .md, .csv or .txt to pick a format".into());
+        return;
+    };
+    let Some(project_id) = default_project_id(app) else {
+        return;
+    };
+    match Import::read(&path, format) {
+        Ok(import) => {
+            app.import = Some(Preview {
+                path,
+                import,
+                project_id,
+                scroll: 0,
+            })
+        }
+        Err(e) => app.set_error_message(e),
+    }
+}
+
+/// The import preview: Enter adds the tasks, Tab picks the next project.
+pub fn handle_import(app: &mut App, key: KeyEvent) {
+    let Some(preview) = app.import.as_mut() else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.import = None,
+        KeyCode::Enter => import_tasks(app),
+        KeyCode::Tab | KeyCode::BackTab => {
+            let projects = Projects::new(app.projects.projects.clone());
+            let ids: Vec<&str> = projects
+                .display_projects
+                .iter()
+                .map(|index| projects.projects[*index].id.as_str())
+                .collect();
+            if let Some(position) = ids.iter().position(|id| *id == preview.project_id) {
+                let step = if key.code == KeyCode::Tab {
+                    1
+                } else {
+                    ids.len() - 1
+                };
+                preview.project_id = ids[(position + step) % ids.len()].to_string();
             }
+        }
+        KeyCode::Char('j') | KeyCode::Down => preview.scroll = preview.scroll.saturating_add(1),
+        KeyCode::Char('k') | KeyCode::Up => preview.scroll = preview.scroll.saturating_sub(1),
+        _ => {}
+    }
+}
+
+/// Queues the tasks of the import preview, subtasks pointing at the
+/// temporary ids of their parents until Todoist has created them.
+fn import_tasks(app: &mut App) {
+    let Some(preview) = app.import.take() else {
+        return;
+    };
+    let project_id = preview.project_id;
+    let project_name = app
+        .projects
+        .projects
+        .iter()
+        .find(|project| project.id == project_id)
+        .map_or(String::new(), |project| project.name.clone());
+
+    let mut section_ids: HashMap<String, String> = app
+        .sections
+        .sections
+        .iter()
+        .filter(|section| section.project_id == project_id)
+        .map(|section| (section.name.to_lowercase(), section.id.clone()))
+        .collect();
+    for name in preview.import.section_names() {
+        if let Entry::Vacant(entry) = section_ids.entry(name.to_lowercase()) {
+            entry.insert(new_section(app, project_id.clone(), name.to_string()));
+        }
+    }
+
+    let mut ids: Vec<String> = Vec::new();
+    for draft in &preview.import.drafts {
+        let section_id = draft
+            .section
+            .as_ref()
+            .and_then(|name| section_ids.get(&name.to_lowercase()));
+        let parent_id = draft.parent.map(|parent| ids[parent].as_str());
+        let json = draft.to_json(&project_id, section_id.map(|id| id.as_str()), parent_id);
+        let task = Task::from_json(outbox::temp_id(), &json);
+        ids.push(task.id.clone());
+        app.tasks.tasks.push(task.clone());
+        app.outbox.push(Mutation::Create {
+            task: Box::new(task),
+            json,
         });
     }
+    app.tasks.filter_task_list();
+    app.notice = Some(format!("Added {}", preview.import.describe(&project_name)));
+}
+
+pub fn handle_prompt(app: &mut App, key: KeyEvent) {
+    let Some(prompt) = app.prompt.as_mut() else {
+        return;
+    };
+    if let (KeyCode::Tab, PromptAction::Export { all }) = (key.code, &mut prompt.action) {
+        *all = !*all;
+        prompt.title = export_title(*all).to_string();
+    } else if key.code == KeyCode::Esc {
+        app.prompt = None;
+    } else if key.code == KeyCode::Enter {
+        let value = prompt.value();
+        if value.is_empty() {
+            return;
+        }
+        let Some(prompt) = app.prompt.take() else {
+            return;
+        };
+        match prompt.action {
+            PromptAction::NewSection { project_id } => create_section(app, project_id, value),
+            PromptAction::RenameSection { section_id } => {
+                let json = json!({ "name": value });
+                let mut renamed = None;
+                app.update_sections(|sections, _| {
+                    if let Some(section) = sections.iter_mut().find(|s| s.id == section_id) {
+                        section.name = value;
+                        renamed = Some(section.clone());
+                    }
+                });
+                if let Some(section) = renamed {
+                    app.outbox.push(Mutation::UpdateSection { section, json });
+                }
+            }
+            PromptAction::Query => match Query::parse(&value) {
+                Ok(_) => {
+                    app.tasks.board = false;
+                    app.tasks.filter = Filter::Query(value);
+                    app.tasks.filter_task_list();
+                    app.projects.selected_project = None;
+                    app.projects.unselect();
+                    app.labels.unselect();
+                    app.filters.unselect();
+                }
+                Err(e) => app.set_error_message(format!("Invalid filter: {}", e)),
+            },
+            PromptAction::NewComment { target } => add_comment(app, target, value),
+            PromptAction::EditComment { comment_id } => edit_comment(app, comment_id, value),
+            PromptAction::Export { all } => export_tasks(app, &value, all),
+            PromptAction::Import => preview_import(app, &value),
+        }
+    } else {
+        prompt.input.input(key);
+    }
+}
+
+fn create_section(app: &mut App, project_id: String, name: String) {
+    let id = new_section(app, project_id, name);
+    app.tasks.select_section(&id);
+}
+
+/// Adds a section at the end of the project, returning its temporary id.
+fn new_section(app: &mut App, project_id: String, name: String) -> String {
+    let order = app
+        .sections
+        .sections
+        .iter()
+        .filter(|section| section.project_id == project_id)
+        .map(|section| section.order)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let json = json!({ "name": name, "project_id": project_id, "order": order });
+    let section = Section::from_json(outbox::temp_id(), &json);
+
+    let id = section.id.clone();
+    let new_section = section.clone();
+    app.update_sections(|sections, _| sections.push(new_section));
+    app.outbox.push(Mutation::CreateSection { section, json });
+    id
+}
+
+/// The section that section actions apply to: the highlighted header, or on a
+/// board the column the cursor is in.
+fn targeted_section(app: &App) -> Option<Section> {
+    if app.tasks.selected_header_id().is_some() || app.tasks.is_board() {
+        app.tasks.selected_section().cloned()
+    } else {
+        None
+    }
+}
+
+/// Swaps the targeted section with its neighbour and renumbers the
+/// project's sections.
+fn move_section(app: &mut App, forward: bool) {
+    let Some(section) = targeted_section(app) else {
+        return;
+    };
+    let mut siblings: Vec<&Section> = app
+        .tasks
+        .sections
+        .iter()
+        .filter(|sibling| sibling.project_id == section.project_id)
+        .collect();
+    let Some(position) = siblings.iter().position(|s| s.id == section.id) else {
+        return;
+    };
+    let target = if forward {
+        position + 1
+    } else if position > 0 {
+        position - 1
+    } else {
+        return;
+    };
+    if target >= siblings.len() {
+        return;
+    }
+    siblings.swap(position, target);
+
+    let orders: Vec<(String, i32)> = siblings
+        .iter()
+        .enumerate()
+        .map(|(i, sibling)| (sibling.id.clone(), i as i32 + 1))
+        .collect();
+    app.update_sections(|sections, _| {
+        for (id, order) in &orders {
+            if let Some(section) = sections.iter_mut().find(|s| s.id == *id) {
+                section.order = *order;
+            }
+        }
+    });
+    app.outbox
+        .push(Mutation::ReorderSections { sections: orders });
+}
+
+/// Section management, only in a project view.
+fn handle_sections(app: &mut App, key: KeyEvent) -> bool {
+    let Filter::ProjectId(project_id) = &app.tasks.filter else {
+        return false;
+    };
+    match key.code {
+        KeyCode::Char('S') => {
+            app.prompt = Some(Prompt::new(
+                "New section",
+                "",
+                PromptAction::NewSection {
+                    project_id: project_id.clone(),
+                },
+            ));
+        }
+        KeyCode::Char('e') => {
+            let Some(section) = targeted_section(app) else {
+                return false;
+            };
+            app.prompt = Some(Prompt::new(
+                "Rename section",
+                &section.name,
+                PromptAction::RenameSection {
+                    section_id: section.id,
+                },
+            ));
+        }
+        KeyCode::Char('{') => move_section(app, false),
+        KeyCode::Char('}') => move_section(app, true),
+        KeyCode::Char('x') | KeyCode::Char('d') => {
+            if app.tasks.selected_header_id().is_none() {
+                return false;
+            }
+            let Some(section) = app.tasks.selected_section() else {
+                return false;
+            };
+            let count = app.tasks.section_len(section);
+            let tasks = match count {
+                0 => String::new(),
+                1 => " and its task".to_string(),
+                n => format!(" and its {} tasks", n),
+            };
+            app.confirmation = Some(Confirmation::new(
+                format!("Delete section \"{}\"{}?", section.name, tasks),
+                Action::DeleteSection(section.id.clone()),
+            ));
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Adds the task in the new task modal and closes it.
+fn create_new_task(app: &mut App) {
+    app.show_new_task = false;
+    let json = app.new_task.get_json();
+
+    let task = Task::from_json(outbox::temp_id(), &json);
+
+    app.tasks.tasks.push(task.clone());
+    app.tasks.filter_task_list();
+
+    app.outbox.push(Mutation::Create {
+        task: Box::new(task),
+        json,
+    });
+}
+
+/// The project being shown, or the inbox.
+fn default_project_id(app: &App) -> Option<String> {
+    match &app.tasks.filter {
+        Filter::ProjectId(project_id) => Some(project_id.clone()),
+        _ => app
+            .projects
+            .projects
+            .iter()
+            .find(|project| project.is_inbox_project)
+            .map(|inbox| inbox.id.clone()),
+    }
+}
+
+/// Opens the new task modal in quick add, for the project being shown or
+/// the inbox, with the label being shown.
+pub fn show_quick_add(app: &mut App) {
+    let Some(project_id) = default_project_id(app) else {
+        return;
+    };
+    app.new_task = new_task::NewTask::new_quick_add(project_id);
+    if let (Filter::Label(name), Some(line)) = (&app.tasks.filter, &mut app.new_task.quick_add) {
+        line.insert_str(format!(" @{}", name));
+        line.move_cursor(CursorMove::Head);
+    }
+    app.show_new_task = true;
+}
+
+/// The single line of quick add: Enter adds the task once everything in it
+/// makes sense, Tab goes on in the full form.
+fn handle_quick_add(app: &mut App, key: KeyEvent) {
+    let parse = |app: &App| {
+        app.new_task.parse_quick_add(
+            &app.projects.projects,
+            &app.sections.sections,
+            &app.collaborators,
+        )
+    };
+    match key.code {
+        KeyCode::Esc => app.show_new_task = false,
+        KeyCode::Enter => {
+            let Some((quick_add, resolved)) = parse(app) else {
+                return;
+            };
+            if resolved.problems.is_empty() && !quick_add.content.is_empty() {
+                app.new_task
+                    .apply_quick_add(&quick_add, &resolved, &app.sections.sections);
+                create_new_task(app);
+            }
+        }
+        KeyCode::Tab => {
+            if let Some((quick_add, resolved)) = parse(app) {
+                app.new_task
+                    .apply_quick_add(&quick_add, &resolved, &app.sections.sections);
+                app.new_task.currently_editing = new_task::CurrentlyEditing::Content;
+            }
+        }
+        _ => {
+            if let Some(line) = &mut app.new_task.quick_add {
+                line.input(key);
+            }
+            // fetch who's in a shared project once someone is assigned
+            let Some((quick_add, resolved)) = parse(app) else {
+                return;
+            };
+            let shared_project = resolved
+                .project_id
+                .filter(|id| !app.collaborators.contains_key(id))
+                .filter(|id| {
+                    app.projects
+                        .projects
+                        .iter()
+                        .any(|project| project.id == *id && project.is_shared)
+                });
+            if let (Some(_), Some(project_id)) = (quick_add.assignee, shared_project) {
+                app.collaborators.insert(project_id.clone(), None);
+                app.fetch_collaborators = Some(project_id);
+            }
+        }
+    }
+}
+
+pub fn handle_new_tasks(app: &mut App, key: KeyEvent) {
+    if app.new_task.quick_add.is_some() {
+        return handle_quick_add(app, key);
+    }
+    if key.code == KeyCode::Esc {
+        app.show_new_task = !app.show_new_task;
+    } else if key.code == KeyCode::Enter {
+        create_new_task(app);
+    }
     if key.code == KeyCode::Tab {
         if app.new_task.currently_editing == new_task::CurrentlyEditing::Content {
             app.new_task.currently_editing = new_task::CurrentlyEditing::Description
         } else if app.new_task.currently_editing == new_task::CurrentlyEditing::Description {
             app.new_task.currently_editing = new_task::CurrentlyEditing::DueString
         } else if app.new_task.currently_editing == new_task::CurrentlyEditing::DueString {
+            app.new_task.currently_editing = new_task::CurrentlyEditing::Labels
+        } else if app.new_task.currently_editing == new_task::CurrentlyEditing::Labels {
+            app.new_task.currently_editing = new_task::CurrentlyEditing::Priority
+        } else if app.new_task.currently_editing == new_task::CurrentlyEditing::Priority
+            && !app.new_task.sections.is_empty()
+        {
+            app.new_task.currently_editing = new_task::CurrentlyEditing::Section
+        } else {
             app.new_task.currently_editing = new_task::CurrentlyEditing::Content
         }
         return;
@@ -155,66 +1089,132 @@ pub fn handle_new_tasks(
         app.new_task.description.input(key);
     } else if app.new_task.currently_editing == new_task::CurrentlyEditing::DueString {
         app.new_task.due_string.input(key);
+    } else if app.new_task.currently_editing == new_task::CurrentlyEditing::Labels {
+        app.new_task.labels.input(key);
+    } else if app.new_task.currently_editing == new_task::CurrentlyEditing::Priority {
+        app.new_task.priority = Some(pick_priority(app.new_task.priority(), key));
+    } else if app.new_task.currently_editing == new_task::CurrentlyEditing::Section {
+        if matches!(
+            key.code,
+            KeyCode::Right | KeyCode::Char(' ') | KeyCode::Char('l')
+        ) {
+            app.new_task.cycle_section(true);
+        } else if key.code == KeyCode::Left {
+            app.new_task.cycle_section(false);
+        }
     }
 }
 
-pub fn handle_tasks(app: &mut App, key: KeyEvent, client: Client) {
+/// Moves the highlighted task to the section of the neighbouring board column.
+fn move_to_neighbouring_section(app: &mut App, forward: bool) {
+    let (Some(index), Some(current)) = (app.tasks.selected_index(), app.tasks.selected_column())
+    else {
+        return;
+    };
+    let columns = app.tasks.columns();
+    let target = if forward {
+        Some(current + 1)
+    } else {
+        current.checked_sub(1)
+    };
+    let Some(column) = target.and_then(|target| columns.get(target)) else {
+        return;
+    };
+
+    let section_id = column
+        .section
+        .map(|section| app.tasks.sections[section].id.clone());
+    let task = &mut app.tasks.tasks[index];
+    task.section_id = section_id;
+    let task = task.clone();
+
+    app.tasks.filter_task_list();
+    app.tasks.select_id(Some(&task.id));
+    app.outbox.push(Mutation::Move {
+        task: Box::new(task),
+    });
+}
+
+pub fn handle_tasks(app: &mut App, key: KeyEvent) {
+    if handle_sections(app, key) {
+        return;
+    }
+
+    if app.tasks.is_board() {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => return app.tasks.move_in_column(true),
+            KeyCode::Char('k') | KeyCode::Up => return app.tasks.move_in_column(false),
+            KeyCode::Right | KeyCode::Char('l') => return app.tasks.move_to_column(true),
+            KeyCode::Left => return app.tasks.move_to_column(false),
+            KeyCode::Char('>') => return move_to_neighbouring_section(app, true),
+            KeyCode::Char('<') => return move_to_neighbouring_section(app, false),
+            _ => {}
+        }
+    }
+
     if key.code == KeyCode::Char('j') || key.code == KeyCode::Down {
         app.tasks.next();
     } else if key.code == KeyCode::Char('k') || key.code == KeyCode::Up {
         app.tasks.previous();
+    } else if key.code == KeyCode::Char('J') {
+        app.tasks.next_section();
+    } else if key.code == KeyCode::Char('K') {
+        app.tasks.previous_section();
+    } else if key.code == KeyCode::Left {
+        if !app.tasks.set_task_expanded(false) && !app.tasks.select_parent() {
+            app.tasks.set_section_collapsed(true);
+        }
+    } else if key.code == KeyCode::Right {
+        if !app.tasks.set_task_expanded(true) {
+            app.tasks.set_section_collapsed(false);
+        }
     } else if key.code == KeyCode::Enter {
-        if let Some(selected) = app.tasks.state.selected() {
-            app.show_task_editor = true;
-            let index = app.tasks.display_tasks[selected];
-            let selected = &app.tasks.tasks[index];
-
-            let mut children = Vec::new();
-
-            for (index, task) in app.tasks.tasks.iter().enumerate() {
-                if task.parent_id == Some(selected.id.clone()) {
-                    children.push(index);
-                }
-            }
-
-            app.task_edit = task_edit::TaskEdit::new(
-                selected.content.clone(),
-                selected.description.clone(),
-                selected.due.as_ref().map_or("", |d| &d.string).to_string(),
-                children,
-                index,
-                task_edit::CurrentlyEditing::Content,
-            );
+        if let Some(TaskRow::Section(section)) = app
+            .tasks
+            .state
+            .selected()
+            .and_then(|i| app.tasks.rows.get(i))
+        {
+            let collapsed = app
+                .tasks
+                .collapsed_sections
+                .contains(&app.tasks.sections[*section].id);
+            app.tasks.set_section_collapsed(!collapsed);
+        } else if let Some(index) = app.tasks.selected_index() {
+            open_task_editor(app, index);
         }
     } else if key.code == KeyCode::Char('x') {
-        if let Some(selected) = app.tasks.state.selected() {
-            let index = app.tasks.display_tasks[selected];
+        if let Some(index) = app.tasks.selected_index() {
             let task_id = app.tasks.tasks[index].id.clone();
-            app.tasks.state = ListState::default();
-            app.tasks.display_tasks.remove(selected);
-            app.tasks.tasks.remove(index);
+            tasks::remove_task(&mut app.tasks.tasks, &task_id);
             app.tasks.filter_task_list();
-            tokio::spawn(async move {
-                close_task(&client, task_id).await.unwrap();
-            });
+            app.outbox.push(Mutation::Close { task_id });
         }
     } else if key.code == KeyCode::Char('n') {
-        if let Some(selected) = app.projects.state.selected() {
-            let selected_id = app.projects.projects[selected].id.clone();
+        if let Some(selected_id) = app.projects.selected_id() {
+            // add to the section the cursor is in
+            let section_id = app
+                .tasks
+                .selected_section()
+                .map(|section| section.id.clone());
             app.show_new_task = true;
             app.new_task = new_task::NewTask::new(selected_id, None);
+            app.new_task.set_sections(&app.tasks.sections, section_id);
+        } else if let Filter::Label(name) = &app.tasks.filter {
+            new_labelled_task(app, name.clone());
         }
     } else if key.code == KeyCode::Char('d') {
-        if let Some(selected) = app.tasks.state.selected() {
-            let index = app.tasks.display_tasks[selected];
+        if let Some(index) = app.tasks.selected_index() {
             let task_id = app.tasks.tasks[index].id.clone();
-            app.tasks.state = ListState::default();
-            app.tasks.display_tasks.remove(selected);
-            app.tasks.tasks.remove(index);
+            tasks::remove_task(&mut app.tasks.tasks, &task_id);
             app.tasks.filter_task_list();
-            tokio::spawn(async move {
-                delete_task(&client, task_id).await.unwrap();
-            });
+            app.outbox.push(Mutation::Delete { task_id });
         }
+    } else if key.code == KeyCode::Char('+') {
+        bump_task_priority(app, true);
+    } else if key.code == KeyCode::Char('-') {
+        bump_task_priority(app, false);
+    } else if key.code == KeyCode::Char('m') && app.show_calendar {
+        reschedule_task(app);
     }
 }
@@ -0,0 +1,87 @@
+use ratatui::widgets::ListState;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default)]
+pub struct Labels {
+    /// Ordered by `order`.
+    pub labels: Vec<Label>,
+    pub state: ListState,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Label {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub color: String,
+    #[serde(default)]
+    pub order: i32,
+    #[serde(default)]
+    pub is_favorite: bool,
+}
+
+impl Labels {
+    pub fn new(mut items: Vec<Label>) -> Labels {
+        items.sort_by_key(|label| label.order);
+        Labels {
+            labels: items,
+            state: ListState::default(),
+        }
+    }
+
+    pub fn next(&mut self) {
+        if self.labels.is_empty() {
+            self.state.select(None);
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i + 1 < self.labels.len() => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        if self.labels.is_empty() {
+            self.state.select(None);
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(0) | None => self.labels.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn unselect(&mut self) {
+        let offset = self.state.offset();
+        self.state.select(None);
+        *self.state.offset_mut() = offset;
+    }
+
+    pub fn selected(&self) -> Option<&Label> {
+        self.state.selected().and_then(|i| self.labels.get(i))
+    }
+
+    pub fn select_name(&mut self, name: Option<&str>) {
+        let i = name.and_then(|name| self.labels.iter().position(|label| label.name == name));
+        self.state.select(i);
+    }
+}
+
+/// Reads labels typed as a comma separated list, `@` in front is optional.
+pub fn parse_labels(text: &str) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for name in text.split(',') {
+        let name = name.trim().trim_start_matches('@').trim();
+        if !name.is_empty() && !labels.iter().any(|label| label == name) {
+            labels.push(name.to_string());
+        }
+    }
+    labels
+}
+
+/// The inverse of `parse_labels`.
+pub fn format_labels(labels: &[String]) -> String {
+    labels.join(", ")
+}
@@ -1,33 +1,62 @@
+use backend::{MemoryBackend, RestBackend, TodoistBackend};
+use cache::Cache;
+use chrono::{DateTime, Local};
 use color_eyre::Result;
+use comments::{Comment, CommentTarget, Comments};
+use config::{BackendKind, Config};
 use crossterm::event::{self, KeyCode, KeyEventKind};
-use dirs::config_dir;
-use key_handler::{handle_new_tasks, handle_projects, handle_task_editor, handle_tasks};
+use error::ApiError;
+use filters::{Filters, SavedFilter};
+use key_handler::{
+    handle_calendar, handle_comments, handle_confirmation, handle_filters, handle_labels,
+    handle_new_tasks, handle_project_editor, handle_projects, handle_prompt, handle_task_editor,
+    handle_tasks,
+};
+use labels::{Label, Labels};
 use new_task::NewTask;
-use projects::Projects;
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
-use sections::Sections;
-use serde::{Deserialize, Serialize};
+use outbox::Outbox;
+use projects::{Collaborator, ListType, Project, Projects};
+use sections::{Section, Sections};
 use std::{
-    fs::{self, File},
-    io::{stdin, BufReader},
-    path::Path,
+    collections::HashMap,
+    env, process,
     sync::{
         mpsc::{self, Receiver, Sender, TryRecvError},
         Arc,
     },
+    time::Instant,
 };
+use sync::{SyncDelta, FULL_SYNC_TOKEN};
 use tasks::{Filter, Task, Tasks};
 use tokio::sync::Mutex;
 
 mod api_calls;
+mod backend;
 mod banner;
+mod cache;
+mod calendar;
+mod cli;
+mod comments;
+mod config;
+mod confirm;
 mod error;
+mod export;
+mod filters;
+mod import;
 mod key_handler;
+mod labels;
 mod new_task;
+mod outbox;
+mod project_edit;
 mod projects;
+mod prompt;
+mod query;
+mod quick_add;
 mod sections;
+mod sync;
 mod task_edit;
 mod tasks;
+mod tree;
 mod tui;
 
 #[derive(Debug, Default)]
@@ -36,10 +65,13 @@ pub enum CurrentScreen {
     Main,
 }
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub enum CurrentFocus {
     #[default]
     Projects,
+    Filters,
+    Labels,
+    Calendar,
     Tasks,
     Help,
     Error,
@@ -53,18 +85,54 @@ pub struct App<'a> {
     pub current_focus: CurrentFocus,
     pub tasks: Tasks,
     pub show_help: bool,
+    /// Rows of the help scrolled past.
+    pub help_scroll: u16,
     pub sections: Sections,
+    pub labels: Labels,
+    pub filters: Filters,
+    /// Whether the account's filters are listed along with the configured ones.
+    pub sync_filters: bool,
+    /// Days covered by the upcoming view.
+    pub upcoming_days: u32,
+    pub show_calendar: bool,
+    pub calendar: calendar::Calendar,
     pub show_task_editor: bool,
     pub task_edit: task_edit::TaskEdit<'a>,
     pub show_new_task: bool,
     pub new_task: NewTask<'a>,
     pub error_message: Option<String>,
     pub show_error: bool,
+    pub last_synced: Option<DateTime<Local>>,
+    pub is_stale: bool,
+    pub is_syncing: bool,
+    pub sync_token: Option<String>,
+    pub outbox: Outbox,
+    pub show_project_editor: bool,
+    pub project_edit: project_edit::ProjectEdit<'a>,
+    /// Waiting for a yes before something destructive happens.
+    pub confirmation: Option<confirm::Confirmation>,
+    pub prompt: Option<prompt::Prompt<'a>>,
+    /// Of the task being edited, or of a project when `show_comments` is set.
+    pub comments: Comments,
+    pub show_comments: bool,
+    /// People in shared projects by project id, `None` while being fetched.
+    pub collaborators: HashMap<String, Option<Vec<Collaborator>>>,
+    /// Set when a project's collaborators should be fetched, the main loop
+    /// does it.
+    pub fetch_collaborators: Option<String>,
+    /// Shown in the status bar until the next key press, e.g. where an
+    /// export went.
+    pub notice: Option<String>,
+    /// Tasks read from a file, shown until they are added or dismissed.
+    pub import: Option<import::Preview>,
 }
 
 impl<'a> App<'a> {
     pub fn new() -> App<'a> {
-        App::default()
+        App {
+            tasks: Tasks::new(Vec::new()),
+            ..Default::default()
+        }
     }
 
     pub fn set_error_message(&mut self, message: String) {
@@ -76,65 +144,424 @@ impl<'a> App<'a> {
         self.error_message = None;
         self.show_error = false;
     }
+
+    pub fn set_data(
+        &mut self,
+        mut projects: Vec<Project>,
+        mut tasks: Vec<Task>,
+        mut sections: Vec<Section>,
+    ) {
+        // Fetched data doesn't know about changes still waiting in the outbox.
+        for mutation in self.outbox.pending() {
+            mutation.apply(&mut projects, &mut tasks, &mut sections);
+        }
+        // Keep the user where they were when the data is reloaded.
+        let filter = std::mem::take(&mut self.tasks.filter);
+        let selected_project = self.projects.selected_project.take();
+        let highlighted_project = self.projects.selected_id();
+        let highlighted_task = self.tasks.selected_id();
+        let collapsed_sections = std::mem::take(&mut self.tasks.collapsed_sections);
+        let expanded_tasks = std::mem::take(&mut self.tasks.expanded_tasks);
+        let collapsed = std::mem::take(&mut self.projects.collapsed);
+        let include_subprojects = self.projects.include_subprojects;
+
+        self.projects = Projects::new(projects);
+        self.projects.collapsed = collapsed;
+        self.projects.include_subprojects = include_subprojects;
+        self.projects.build_tree();
+        self.projects.selected_project = selected_project;
+        self.projects.select_id(highlighted_project.as_deref());
+        self.tasks = Tasks::new(tasks);
+        self.tasks.set_projects(&self.projects.projects);
+        if let Filter::ProjectId(project_id) = &filter {
+            // sub-projects may have been added or moved, and the layout changed
+            if include_subprojects {
+                self.tasks.subproject_ids = self.projects.descendants(project_id);
+            }
+            self.tasks.board =
+                self.projects.projects.iter().any(|project| {
+                    project.id == *project_id && project.view_style == ListType::Board
+                });
+        }
+        self.tasks.filter = filter;
+        self.tasks.collapsed_sections = collapsed_sections;
+        self.tasks.expanded_tasks = expanded_tasks;
+        self.tasks.set_sections(&sections);
+        self.sections = Sections::new(sections);
+        self.tasks.filter_task_list();
+        self.tasks.select_id(highlighted_task.as_deref());
+    }
+
+    /// Replaces the labels, keeping the highlighted one.
+    pub fn set_labels(&mut self, labels: Vec<Label>) {
+        let highlighted = self.labels.selected().map(|label| label.name.clone());
+        self.labels = Labels::new(labels);
+        self.labels.select_name(highlighted.as_deref());
+    }
+
+    /// Shows fetched comments along with the changes to them still waiting
+    /// in the outbox.
+    pub fn set_comments(&mut self, target: &CommentTarget, mut comments: Vec<Comment>) {
+        for mutation in self.outbox.pending() {
+            mutation.apply_to_comments(target, &mut comments);
+        }
+        self.comments.set_comments(target, comments);
+    }
+
+    /// The pane Tab moves to, skipping empty ones.
+    pub fn next_focus(&self) -> CurrentFocus {
+        let panes = [
+            CurrentFocus::Projects,
+            CurrentFocus::Filters,
+            CurrentFocus::Labels,
+            CurrentFocus::Calendar,
+            CurrentFocus::Tasks,
+        ];
+        let Some(current) = panes.iter().position(|pane| *pane == self.current_focus) else {
+            return self.current_focus;
+        };
+        panes
+            .iter()
+            .cycle()
+            .skip(current + 1)
+            .find(|pane| match pane {
+                CurrentFocus::Filters => !self.filters.filters.is_empty(),
+                CurrentFocus::Labels => !self.labels.labels.is_empty(),
+                CurrentFocus::Calendar => self.show_calendar,
+                _ => true,
+            })
+            .copied()
+            .unwrap_or(self.current_focus)
+    }
+
+    /// Points everything at the id Todoist gave a project created offline.
+    pub fn replace_project_id(&mut self, temp_id: &str, project: Project) {
+        let id = project.id.clone();
+        for existing in &mut self.projects.projects {
+            if existing.id == temp_id {
+                *existing = project.clone();
+            } else if existing.parent_id.as_deref() == Some(temp_id) {
+                existing.parent_id = Some(id.clone());
+            }
+        }
+        for task in &mut self.tasks.tasks {
+            if task.project_id == temp_id {
+                task.project_id = id.clone();
+            }
+        }
+        self.comments.replace_id(temp_id, &id);
+        if self.projects.collapsed.remove(temp_id) {
+            self.projects.collapsed.insert(id.clone());
+        }
+        if self.projects.selected_project.as_deref() == Some(temp_id) {
+            self.projects.selected_project = Some(id.clone());
+        }
+        if let Filter::ProjectId(project_id) = &mut self.tasks.filter {
+            if project_id == temp_id {
+                *project_id = id;
+            }
+        }
+        self.projects.build_tree();
+        self.tasks.set_projects(&self.projects.projects);
+        self.tasks.filter_task_list();
+    }
+
+    /// Drops a project, its sub-projects and their tasks, and leaves its
+    /// view if it was showing.
+    pub fn remove_project(&mut self, id: &str) {
+        projects::remove_project(&mut self.projects.projects, &mut self.tasks.tasks, id);
+        let highlighted = self.projects.selected_id();
+        self.projects.build_tree();
+        self.tasks.set_projects(&self.projects.projects);
+        self.projects.select_id(highlighted.as_deref());
+
+        let shown = match &self.tasks.filter {
+            Filter::ProjectId(project_id) => {
+                !self.projects.projects.iter().any(|p| p.id == *project_id)
+            }
+            _ => false,
+        };
+        if shown {
+            self.tasks.filter = Filter::default();
+            self.projects.selected_project = None;
+            self.projects.unselect();
+        }
+        self.tasks.filter_task_list();
+    }
+
+    /// Changes the sections, and the tasks along with them, keeping the
+    /// cursor where it was.
+    pub fn update_sections(&mut self, change: impl FnOnce(&mut Vec<Section>, &mut Vec<Task>)) {
+        let highlighted_task = self.tasks.selected_id();
+        let highlighted_section = self.tasks.selected_header_id();
+
+        let mut sections = std::mem::take(&mut self.sections.sections);
+        change(&mut sections, &mut self.tasks.tasks);
+        self.tasks.set_sections(&sections);
+        self.sections = Sections::new(sections);
+        self.tasks.filter_task_list();
+
+        match highlighted_section {
+            Some(id) => self.tasks.select_section(&id),
+            None => self.tasks.select_id(highlighted_task.as_deref()),
+        }
+    }
+
+    pub fn apply_sync(&mut self, delta: SyncDelta) {
+        self.sync_token = Some(delta.sync_token);
+        self.last_synced = Some(Local::now());
+        self.is_stale = false;
+
+        if self.sync_filters {
+            let mut filters = if delta.full_sync {
+                Vec::new()
+            } else {
+                self.filters.account().to_vec()
+            };
+            delta.filters.merge_into(&mut filters, |filter| {
+                filter.id.as_deref().unwrap_or_default()
+            });
+            self.filters.set_account(filters);
+        }
+
+        if delta.full_sync {
+            self.set_labels(delta.labels.updated);
+            self.set_data(
+                delta.projects.updated,
+                delta.tasks.updated,
+                delta.sections.updated,
+            );
+            return;
+        }
+
+        let mut projects = self.projects.projects.clone();
+        let mut tasks = self.tasks.tasks.clone();
+        let mut sections = self.sections.sections.clone();
+        let mut labels = self.labels.labels.clone();
+        delta
+            .projects
+            .merge_into(&mut projects, |project| &project.id);
+        delta.tasks.merge_into(&mut tasks, |task| &task.id);
+        delta
+            .sections
+            .merge_into(&mut sections, |section| &section.id);
+        delta.labels.merge_into(&mut labels, |label| &label.id);
+        self.set_labels(labels);
+        self.set_data(projects, tasks, sections);
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum TaskResult {
-    Task(Task),
+    Task(Box<Task>),
+    /// A task created locally now exists in Todoist under a new id.
+    Created {
+        temp_id: String,
+        task: Box<Task>,
+    },
+    /// A task created locally that Todoist refused.
+    Removed(String),
+    Project(Box<Project>),
+    /// A project created locally now exists in Todoist under a new id.
+    ProjectCreated {
+        temp_id: String,
+        project: Box<Project>,
+    },
+    /// A project created locally that Todoist refused.
+    ProjectRemoved(String),
+    Section(Section),
+    /// A section created locally now exists in Todoist under a new id.
+    SectionCreated {
+        temp_id: String,
+        section: Section,
+    },
+    /// A section created locally that Todoist refused.
+    SectionRemoved(String),
+    Collaborators {
+        project_id: String,
+        collaborators: Result<Vec<Collaborator>, ApiError>,
+    },
+    Comments {
+        target: CommentTarget,
+        comments: Result<Vec<Comment>, ApiError>,
+    },
+    Comment(Comment),
+    /// A comment added locally now exists in Todoist under a new id.
+    CommentCreated {
+        temp_id: String,
+        comment: Comment,
+    },
+    /// A comment added locally that Todoist refused.
+    CommentRemoved(String),
+    /// Everything, fetched from scratch. Each resource fails on its own.
+    Loaded {
+        projects: Result<Vec<Project>, ApiError>,
+        tasks: Result<Vec<Task>, ApiError>,
+        sections: Result<Vec<Section>, ApiError>,
+        labels: Result<Vec<Label>, ApiError>,
+        /// Empty when the account's filters aren't wanted.
+        filters: Result<Vec<SavedFilter>, ApiError>,
+    },
+    Synced(Box<SyncDelta>),
+    SyncFailed(ApiError),
+    /// A change to something Todoist already had was discarded, what is
+    /// shown still has it until everything is fetched again.
+    Discarded,
     Error(String),
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        run_command(&args).await;
+        return Ok(());
+    }
+
+    let config = config::get_config();
+
+    match config.backend() {
+        BackendKind::Rest => {
+            let backend = RestBackend::new(&config.bearer_token, config.base_url());
+            run(backend, &config, true).await
+        }
+        BackendKind::Memory => run(MemoryBackend::demo(), &config, false).await,
+    }
+}
+
+/// Runs a command given on the command line instead of opening the interface.
+async fn run_command(args: &[String]) {
+    let command = match cli::Command::parse(args) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("todoist: {}\nRun `todoist help` to see the commands", error);
+            process::exit(2);
+        }
+    };
+    if command == cli::Command::Help {
+        cli::print(cli::USAGE);
+        return;
+    }
+    let Some(config) = config::saved_config() else {
+        eprintln!("todoist: no config found, run todoist without arguments to set it up");
+        process::exit(1);
+    };
+    let result = match config.backend() {
+        BackendKind::Rest => {
+            let backend = RestBackend::new(&config.bearer_token, config.base_url());
+            cli::run(&backend, command).await
+        }
+        BackendKind::Memory => cli::run(&MemoryBackend::demo(), command).await,
+    };
+    if let Err(error) = result {
+        eprintln!("todoist: {}", error);
+        process::exit(1);
+    }
+}
+
+/// `persist` keeps the cache and outbox on disk between sessions.
+async fn run<B: TodoistBackend>(backend: B, config: &Config, persist: bool) -> Result<()> {
+    let filters = config.saved_filters().unwrap_or_else(|error| {
+        eprintln!("todoist: invalid filters in the config, {}", error);
+        process::exit(1);
+    });
     let (tx, rx): (Sender<TaskResult>, Receiver<TaskResult>) = mpsc::channel();
-    let bearer_token = get_token();
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        AUTHORIZATION,
-        HeaderValue::from_str(&format!("Bearer {}", bearer_token)).unwrap(),
-    );
-
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()
-        .unwrap();
+    let cache_path = persist.then(Cache::default_path);
 
     error::install_hooks()?;
     let mut terminal = tui::init()?;
-    let app = Arc::new(Mutex::new(App::new()));
-
-    let app_clone = Arc::clone(&app);
-    let client_clone = client.clone();
-    let initialise_task = tokio::spawn(async move {
-        // todo: make network calls parallel
-        let project_resp = api_calls::fetch_projects(&client_clone).await.unwrap();
-        let projects = Projects::new(project_resp);
-        let task_resp = api_calls::fetch_tasks(&client_clone).await.unwrap();
-        let tasks = Tasks::new(task_resp);
-        let mut app = app_clone.lock().await;
-        let sections_resp = api_calls::fetch_sections(&client_clone).await.unwrap();
-        let sections = Sections::new(sections_resp);
-        app.projects = projects;
-        app.tasks = tasks;
-        app.sections = sections;
-        app.tasks.filter_task_list();
-        app.tasks.find_tasks_with_children();
-    });
+    let mut app = App::new();
+    let (outbox, outbox_error) = Outbox::load(persist.then(Outbox::default_path));
+    app.outbox = outbox;
+    if let Some(error) = outbox_error {
+        app.set_error_message(error);
+    }
+    app.filters = Filters::new(filters);
+    app.sync_filters = config.sync_filters();
+    app.upcoming_days = config.upcoming_days();
+    if !app.filters.filters.is_empty() {
+        key_handler::show_filter(&mut app, 0);
+    }
+    tokio::spawn(outbox::run(app.outbox.clone(), backend.clone(), tx.clone()));
+    if let Some(cache) = cache_path.as_deref().and_then(Cache::load) {
+        app.last_synced = cache.saved_at();
+        app.is_stale = true;
+        app.sync_token = cache.sync_token;
+        app.set_labels(cache.labels);
+        if app.sync_filters {
+            app.filters.set_account(cache.filters);
+        }
+        app.set_data(cache.projects, cache.tasks, cache.sections);
+    }
+    let app = Arc::new(Mutex::new(app));
+
+    app.lock().await.is_syncing = true;
+    spawn_reload(&backend, config.sync_filters(), tx.clone());
+
+    let refresh_interval = config.refresh_interval();
+    let mut last_refresh = Instant::now();
 
     loop {
         let mut app = app.lock().await;
         terminal.draw(|frame| tui::ui(frame, &mut app))?;
 
+        if std::mem::take(&mut app.comments.fetch) {
+            if let Some(target) = app.comments.target.clone() {
+                spawn_comments(&backend, target, tx.clone());
+            }
+        }
+        if let Some(project_id) = app.fetch_collaborators.take() {
+            spawn_collaborators(&backend, project_id, tx.clone());
+        }
+
+        if app.is_syncing {
+            last_refresh = Instant::now();
+        } else if refresh_interval.is_some_and(|interval| last_refresh.elapsed() >= interval) {
+            app.is_syncing = true;
+            last_refresh = Instant::now();
+            spawn_sync(&backend, &app, tx.clone());
+        }
+
         if event::poll(std::time::Duration::from_millis(150))? {
             if let event::Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                    app.notice = None;
+                    if app.confirmation.is_some() {
+                        handle_confirmation(&mut app, key);
+                        continue;
+                    }
+
+                    if app.prompt.is_some() {
+                        handle_prompt(&mut app, key);
+                        continue;
+                    }
+
+                    if app.import.is_some() {
+                        key_handler::handle_import(&mut app, key);
+                        continue;
+                    }
+
                     if app.show_task_editor {
-                        handle_task_editor(&mut app, key, client.clone(), tx.clone());
+                        handle_task_editor(&mut app, key);
                         continue;
                     }
 
                     if app.show_new_task {
-                        handle_new_tasks(&mut app, key, client.clone(), tx.clone());
+                        handle_new_tasks(&mut app, key);
+                        continue;
+                    }
+
+                    if app.show_project_editor {
+                        handle_project_editor(&mut app, key);
+                        continue;
+                    }
+
+                    if app.show_comments {
+                        if key.code == KeyCode::Esc {
+                            app.show_comments = false;
+                        } else {
+                            handle_comments(&mut app, key);
+                        }
                         continue;
                     }
 
@@ -147,20 +574,57 @@ async fn main() -> Result<()> {
 
                     if key.code == KeyCode::Char('h') {
                         app.show_help = !app.show_help;
+                        app.help_scroll = 0;
                     } else if key.code == KeyCode::Char('q') {
                         break;
-                    } else if key.code == KeyCode::Char('t') {
-                        app.tasks.filter = Filter::Today;
-                        app.tasks.filter_task_list();
-                        app.projects.unselect();
-                    } else if key.code == KeyCode::Char('o') {
-                        app.tasks.filter = Filter::Overdue;
-                        app.tasks.filter_task_list();
-                        app.projects.unselect();
-                    } else if key.code == KeyCode::Char('a') {
-                        app.tasks.filter = Filter::All;
-                        app.tasks.filter_task_list();
-                        app.projects.unselect();
+                    } else if let Some(index) = match key.code {
+                        KeyCode::Char(c) => app.filters.position_of_key(c),
+                        _ => None,
+                    } {
+                        key_handler::show_filter(&mut app, index);
+                        continue;
+                    } else if key.code == KeyCode::Char('Q') {
+                        key_handler::show_quick_add(&mut app);
+                        continue;
+                    } else if key.code == KeyCode::Char('E') {
+                        key_handler::show_export(&mut app);
+                        continue;
+                    } else if key.code == KeyCode::Char('I') {
+                        key_handler::show_import(&mut app);
+                        continue;
+                    } else if key.code == KeyCode::Char('u') {
+                        key_handler::show_upcoming(&mut app);
+                    } else if key.code == KeyCode::Char('c') {
+                        app.show_calendar = !app.show_calendar;
+                        app.current_focus = if app.show_calendar {
+                            CurrentFocus::Calendar
+                        } else if app.current_focus == CurrentFocus::Calendar {
+                            CurrentFocus::Tasks
+                        } else {
+                            app.current_focus
+                        };
+                        continue;
+                    } else if key.code == KeyCode::Char('/') {
+                        let query = match &app.tasks.filter {
+                            Filter::Query(text) => text.clone(),
+                            _ => String::new(),
+                        };
+                        app.prompt = Some(prompt::Prompt::new(
+                            "Filter",
+                            &query,
+                            prompt::PromptAction::Query,
+                        ));
+                        continue;
+                    } else if key.code == KeyCode::Char('r') {
+                        if !app.is_syncing {
+                            app.is_syncing = true;
+                            spawn_sync(&backend, &app, tx.clone());
+                        }
+                    } else if key.code == KeyCode::Char('R') {
+                        if !app.is_syncing {
+                            app.is_syncing = true;
+                            spawn_reload(&backend, app.sync_filters, tx.clone());
+                        }
                     } else if key.code == KeyCode::Char('p') {
                         app.tasks.sort_tasks(tasks::SortCriterion::Priority);
                     } else if key.code == KeyCode::Char('d') {
@@ -168,21 +632,32 @@ async fn main() -> Result<()> {
                     }
 
                     if app.show_help {
+                        match key.code {
+                            KeyCode::Char('j') | KeyCode::Down => {
+                                app.help_scroll = app.help_scroll.saturating_add(1)
+                            }
+                            KeyCode::Char('k') | KeyCode::Up => {
+                                app.help_scroll = app.help_scroll.saturating_sub(1)
+                            }
+                            _ => {}
+                        }
                         continue;
                     }
 
                     if key.code == KeyCode::Tab {
-                        match app.current_focus {
-                            CurrentFocus::Projects => app.current_focus = CurrentFocus::Tasks,
-                            CurrentFocus::Tasks => app.current_focus = CurrentFocus::Projects,
-                            _ => {}
-                        }
+                        app.current_focus = app.next_focus();
                     }
 
                     if app.current_focus == CurrentFocus::Projects {
                         handle_projects(&mut app, key)
+                    } else if app.current_focus == CurrentFocus::Filters {
+                        handle_filters(&mut app, key)
+                    } else if app.current_focus == CurrentFocus::Labels {
+                        handle_labels(&mut app, key)
+                    } else if app.current_focus == CurrentFocus::Calendar {
+                        handle_calendar(&mut app, key)
                     } else if app.current_focus == CurrentFocus::Tasks {
-                        handle_tasks(&mut app, key, client.clone())
+                        handle_tasks(&mut app, key)
                     }
                 }
             }
@@ -194,16 +669,225 @@ async fn main() -> Result<()> {
                     let mut task_exists = false;
                     for existing_task in &mut app.tasks.tasks {
                         if existing_task.id == task.id {
-                            *existing_task = task.clone();
+                            *existing_task = (*task).clone();
                             task_exists = true;
                             break;
                         }
                     }
 
                     if !task_exists {
-                        app.tasks.tasks.push(task);
+                        app.tasks.tasks.push(*task);
                     }
+                    let highlighted = app.tasks.selected_id();
                     app.tasks.filter_task_list();
+                    app.tasks.select_id(highlighted.as_deref());
+                }
+                TaskResult::Created { temp_id, task } => {
+                    for existing_task in &mut app.tasks.tasks {
+                        if existing_task.id == temp_id {
+                            *existing_task = (*task).clone();
+                        } else if existing_task.parent_id.as_ref() == Some(&temp_id) {
+                            existing_task.parent_id = Some(task.id.clone());
+                        }
+                    }
+                    if app.tasks.expanded_tasks.remove(&temp_id) {
+                        app.tasks.expanded_tasks.insert(task.id.clone());
+                    }
+                    app.comments.replace_id(&temp_id, &task.id);
+                    app.task_edit.replace_id(&temp_id, &task.id);
+                    app.tasks.filter_task_list();
+                }
+                TaskResult::Removed(task_id) => {
+                    app.tasks.tasks.retain(|task| task.id != task_id);
+                    app.tasks.filter_task_list();
+                }
+                TaskResult::Project(project) => {
+                    if let Some(existing) = app
+                        .projects
+                        .projects
+                        .iter_mut()
+                        .find(|existing| existing.id == project.id)
+                    {
+                        // a move queued after the update is still on its way
+                        let parent_id = existing.parent_id.take();
+                        *existing = *project;
+                        existing.parent_id = parent_id;
+                    }
+                    app.projects.build_tree();
+                    app.tasks.projects = app.projects.projects.clone();
+                }
+                TaskResult::ProjectCreated { temp_id, project } => {
+                    app.replace_project_id(&temp_id, *project);
+                }
+                TaskResult::ProjectRemoved(project_id) => {
+                    app.remove_project(&project_id);
+                }
+                TaskResult::Section(section) => {
+                    app.update_sections(|sections, _| {
+                        if let Some(existing) = sections
+                            .iter_mut()
+                            .find(|existing| existing.id == section.id)
+                        {
+                            existing.name = section.name;
+                        }
+                    });
+                }
+                TaskResult::SectionCreated { temp_id, section } => {
+                    if app.tasks.collapsed_sections.remove(&temp_id) {
+                        app.tasks.collapsed_sections.insert(section.id.clone());
+                    }
+                    let highlighted = app.tasks.selected_header_id() == Some(temp_id.clone());
+                    let section_id = section.id.clone();
+                    app.update_sections(|sections, tasks| {
+                        for task in tasks {
+                            if task.section_id.as_ref() == Some(&temp_id) {
+                                task.section_id = Some(section.id.clone());
+                            }
+                        }
+                        if let Some(existing) =
+                            sections.iter_mut().find(|existing| existing.id == temp_id)
+                        {
+                            // keep any reordering done in the meantime
+                            let order = existing.order;
+                            *existing = section;
+                            existing.order = order;
+                        }
+                    });
+                    if highlighted {
+                        app.tasks.select_section(&section_id);
+                    }
+                }
+                TaskResult::SectionRemoved(section_id) => {
+                    app.update_sections(|sections, tasks| {
+                        sections.retain(|section| section.id != section_id);
+                        tasks.retain(|task| task.section_id.as_ref() != Some(&section_id));
+                    });
+                }
+                TaskResult::Collaborators {
+                    project_id,
+                    collaborators,
+                } => {
+                    let collaborators = collaborators.unwrap_or_else(|e| {
+                        app.set_error_message(format!(
+                            "Could not fetch who's in the project: {}",
+                            e
+                        ));
+                        Vec::new()
+                    });
+                    app.collaborators.insert(project_id, Some(collaborators));
+                }
+                TaskResult::Comments { target, comments } => match comments {
+                    Ok(comments) => app.set_comments(&target, comments),
+                    Err(e) => {
+                        if app.comments.target.as_ref() == Some(&target) {
+                            app.comments.error = Some(e.to_string());
+                        }
+                    }
+                },
+                TaskResult::Comment(comment) => {
+                    if let Some(existing) = app
+                        .comments
+                        .comments
+                        .iter_mut()
+                        .find(|existing| existing.id == comment.id)
+                    {
+                        *existing = comment;
+                    }
+                }
+                TaskResult::CommentCreated { temp_id, comment } => {
+                    let highlighted = app.comments.selected().map(|c| c.id.clone());
+                    if let Some(existing) = app
+                        .comments
+                        .comments
+                        .iter_mut()
+                        .find(|existing| existing.id == temp_id)
+                    {
+                        *existing = comment.clone();
+                    }
+                    if highlighted == Some(temp_id) {
+                        app.comments.select_id(Some(&comment.id));
+                    }
+                }
+                TaskResult::CommentRemoved(comment_id) => {
+                    app.comments
+                        .comments
+                        .retain(|comment| comment.id != comment_id);
+                }
+                TaskResult::Loaded {
+                    projects,
+                    tasks,
+                    sections,
+                    labels,
+                    filters,
+                } => {
+                    app.is_syncing = false;
+                    let mut errors = Vec::new();
+                    let projects = projects.unwrap_or_else(|e| {
+                        errors.push(("projects", e));
+                        app.projects.projects.clone()
+                    });
+                    let tasks = tasks.unwrap_or_else(|e| {
+                        errors.push(("tasks", e));
+                        app.tasks.tasks.clone()
+                    });
+                    let sections = sections.unwrap_or_else(|e| {
+                        errors.push(("sections", e));
+                        app.sections.sections.clone()
+                    });
+                    let labels = labels.unwrap_or_else(|e| {
+                        errors.push(("labels", e));
+                        app.labels.labels.clone()
+                    });
+                    app.set_labels(labels);
+                    match filters {
+                        Ok(filters) => app.filters.set_account(filters),
+                        Err(e) => errors.push(("filters", e)),
+                    }
+                    app.set_data(projects, tasks, sections);
+
+                    if errors.is_empty() {
+                        app.last_synced = Some(Local::now());
+                        app.is_stale = false;
+                        if let Some(path) = &cache_path {
+                            let _ = Cache::from_app(&app).save(path);
+                        }
+                    } else if app.last_synced.is_some()
+                        && errors
+                            .iter()
+                            .all(|(_, e)| matches!(e, ApiError::Network(_)))
+                    {
+                        // Stay quiet about being offline when there is cached data to
+                        // show, the status line already says it's stale.
+                        app.is_stale = true;
+                    } else {
+                        let message = errors
+                            .iter()
+                            .map(|(resource, e)| format!("Could not load {}: {}", resource, e))
+                            .collect::<Vec<_>>()
+                            .join("\n\n");
+                        app.set_error_message(message);
+                    }
+                }
+                TaskResult::Synced(delta) => {
+                    app.is_syncing = false;
+                    app.apply_sync(*delta);
+                    if let Some(path) = &cache_path {
+                        let _ = Cache::from_app(&app).save(path);
+                    }
+                }
+                TaskResult::SyncFailed(e) => {
+                    app.is_syncing = false;
+                    match e {
+                        ApiError::Network(_) if app.last_synced.is_some() => app.is_stale = true,
+                        e => app.set_error_message(format!("Failed to sync: {}", e)),
+                    }
+                }
+                TaskResult::Discarded => {
+                    // without a token the next sync starts over too
+                    app.sync_token = None;
+                    app.comments.fetch = true;
+                    app.is_syncing = true;
+                    spawn_reload(&backend, app.sync_filters, tx.clone());
                 }
                 TaskResult::Error(error_msg) => {
                     app.set_error_message(error_msg);
@@ -214,69 +898,247 @@ async fn main() -> Result<()> {
         }
     }
     tui::restore()?;
-    let _ = initialise_task.await;
+    let app = app.lock().await;
+    if let (Some(path), Some(_)) = (cache_path, app.last_synced) {
+        let _ = Cache::from_app(&app).save(&path);
+    }
     Ok(())
 }
 
-#[derive(Deserialize, Serialize)]
-struct Config {
-    bearer_token: String,
-}
-
-fn get_token() -> String {
-    let mut client_key = String::new();
-    match config_dir() {
-        Some(home) => {
-            let path = Path::new(&home);
-            let config_dir = path.join(".todoist");
-            if !config_dir.exists() {
-                fs::create_dir(&config_dir).unwrap();
+/// Fetches everything from scratch in the background, the result arrives as
+/// `TaskResult::Loaded`. The account's filters are only fetched when
+/// `sync_filters` is set.
+fn spawn_reload<B: TodoistBackend>(backend: &B, sync_filters: bool, tx: Sender<TaskResult>) {
+    let backend = backend.clone();
+    tokio::spawn(async move {
+        let filters = async {
+            if sync_filters {
+                backend.fetch_filters().await
+            } else {
+                Ok(Vec::new())
             }
-            let config_file_path = &config_dir.join("config.json");
+        };
+        let (projects, tasks, sections, labels, filters) = tokio::join!(
+            backend.fetch_projects(),
+            backend.fetch_tasks(),
+            backend.fetch_sections(),
+            backend.fetch_labels(),
+            filters
+        );
+        let _ = tx.send(TaskResult::Loaded {
+            projects,
+            tasks,
+            sections,
+            labels,
+            filters,
+        });
+    });
+}
 
-            if config_file_path.exists() {
-                let file = File::open(config_file_path).unwrap();
-                let reader = BufReader::new(file);
+/// Fetches the comments of a task or project in the background, they
+/// arrive as `TaskResult::Comments`.
+fn spawn_comments<B: TodoistBackend>(backend: &B, target: CommentTarget, tx: Sender<TaskResult>) {
+    let backend = backend.clone();
+    tokio::spawn(async move {
+        let comments = backend.fetch_comments(target.clone()).await;
+        let _ = tx.send(TaskResult::Comments { target, comments });
+    });
+}
 
-                let config: Config = serde_json::from_reader(reader).unwrap();
-                client_key = config.bearer_token;
+/// Fetches the people in a shared project in the background, they arrive
+/// as `TaskResult::Collaborators`.
+fn spawn_collaborators<B: TodoistBackend>(backend: &B, project_id: String, tx: Sender<TaskResult>) {
+    let backend = backend.clone();
+    tokio::spawn(async move {
+        let collaborators = backend.fetch_collaborators(project_id.clone()).await;
+        let _ = tx.send(TaskResult::Collaborators {
+            project_id,
+            collaborators,
+        });
+    });
+}
 
-                println!("Your config is saved at {}", config_file_path.display());
-            } else {
-                println!("{}", banner::BANNER);
+/// Asks for the changes since the last sync in the background, the result
+/// arrives as `TaskResult::Synced`.
+fn spawn_sync<B: TodoistBackend>(backend: &B, app: &App, tx: Sender<TaskResult>) {
+    let backend = backend.clone();
+    let sync_token = app
+        .sync_token
+        .clone()
+        .unwrap_or_else(|| FULL_SYNC_TOKEN.to_string());
+    tokio::spawn(async move {
+        let result = match backend.sync(sync_token).await {
+            Ok(delta) => TaskResult::Synced(Box::new(delta)),
+            Err(e) => TaskResult::SyncFailed(e),
+        };
+        let _ = tx.send(result);
+    });
+}
 
-                println!();
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
 
-                println!("\nHow to get setup:");
-                println!("-----------------\n");
+    use crossterm::event::{KeyEvent, KeyModifiers};
 
-                let instructions = [
-                    "Go to the todoist integrations - https://app.todoist.com/app/settings/integrations/developer",
-                    "Under the developer tab, you will be able to see the API Token",
-                    "Copy the token and paste it below",
-                    "You are now ready to authenticate with Todoist!",
-                    &format!("Config will be saved to {}\n\n", config_file_path.display()),
-                  ];
+    use super::*;
 
-                let mut number = 1;
-                for item in instructions.iter() {
-                    println!("  {}. {}", number, item);
-                    number += 1;
-                }
+    fn press(app: &mut App, handler: fn(&mut App, KeyEvent), keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                c => KeyCode::Char(c),
+            };
+            handler(app, KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
 
-                println!("\nEnter your API token:");
-                stdin().read_line(&mut client_key).unwrap();
-                client_key = client_key.trim().to_string(); // Trim the newline character
+    /// Loads the account the way the interface does, showing all tasks.
+    async fn load(backend: &MemoryBackend) -> App<'static> {
+        let (tx, rx) = mpsc::channel();
+        spawn_reload(backend, false, tx);
+        let result = tokio::task::spawn_blocking(move || rx.recv_timeout(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        let Ok(TaskResult::Loaded {
+            projects,
+            tasks,
+            sections,
+            ..
+        }) = result
+        else {
+            panic!("nothing was loaded");
+        };
 
-                let config = Config {
-                    bearer_token: client_key.clone(),
-                };
+        let mut app = App::new();
+        app.filters = Filters::new(SavedFilter::defaults());
+        app.set_data(projects.unwrap(), tasks.unwrap(), sections.unwrap());
+        let all = app.filters.position_of_key('a').unwrap();
+        key_handler::show_filter(&mut app, all);
+        app
+    }
 
-                let config_json = serde_json::to_string(&config).unwrap();
-                fs::write(config_file_path, config_json).unwrap();
+    /// Sends whatever the key handlers queued, returns what the interface
+    /// was told.
+    async fn send_outbox(app: &App<'_>, backend: &MemoryBackend) -> Vec<TaskResult> {
+        let (tx, rx) = mpsc::channel();
+        let sender = tokio::spawn(outbox::run(app.outbox.clone(), backend.clone(), tx));
+        for _ in 0..500 {
+            if app.outbox.pending_count() == 0 {
+                break;
             }
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
-        None => panic!("No directory found"),
+        sender.abort();
+        assert_eq!(app.outbox.pending_count(), 0, "the outbox wasn't sent");
+        rx.try_iter().collect()
+    }
+
+    async fn contents(backend: &MemoryBackend) -> Vec<String> {
+        let tasks = backend.fetch_tasks().await.unwrap();
+        tasks.into_iter().map(|task| task.content).collect()
+    }
+
+    #[tokio::test]
+    async fn loads_the_account() {
+        let app = load(&MemoryBackend::demo()).await;
+        assert_eq!(app.projects.projects.len(), 1);
+        assert_eq!(app.tasks.tasks.len(), 2);
+        assert_eq!(app.tasks.display_tasks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn completes_a_task() {
+        let backend = MemoryBackend::demo();
+        let mut app = load(&backend).await;
+        press(&mut app, handle_tasks, "jx");
+        assert_eq!(app.tasks.tasks.len(), 1);
+
+        send_outbox(&app, &backend).await;
+        assert_eq!(
+            contents(&backend).await,
+            ["Point base_url at a real server"]
+        );
+    }
+
+    #[tokio::test]
+    async fn completes_a_task_with_its_subtasks() {
+        let backend = MemoryBackend::demo();
+        let child = backend
+            .create_task(serde_json::json!({ "content": "Child", "parent_id": "mem-1" }))
+            .await
+            .unwrap();
+        backend
+            .create_task(serde_json::json!({ "content": "Grandchild", "parent_id": child.id }))
+            .await
+            .unwrap();
+        let mut app = load(&backend).await;
+        press(&mut app, handle_tasks, "jx");
+        assert_eq!(app.tasks.tasks.len(), 1);
+
+        send_outbox(&app, &backend).await;
+        assert_eq!(
+            contents(&backend).await,
+            ["Point base_url at a real server"]
+        );
+    }
+
+    #[tokio::test]
+    async fn edits_a_task() {
+        let backend = MemoryBackend::demo();
+        let mut app = load(&backend).await;
+        press(&mut app, handle_tasks, "j\n");
+        assert!(app.show_task_editor);
+        press(&mut app, handle_task_editor, "Now \n");
+        assert!(!app.show_task_editor);
+        assert_eq!(app.tasks.tasks[0].content, "Now Try out the todoist TUI");
+
+        send_outbox(&app, &backend).await;
+        assert_eq!(
+            contents(&backend).await,
+            [
+                "Now Try out the todoist TUI",
+                "Point base_url at a real server"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn enter_in_the_comments_keeps_the_editor_open() {
+        let backend = MemoryBackend::demo();
+        let mut app = load(&backend).await;
+        press(&mut app, handle_tasks, "j\n");
+        press(&mut app, handle_task_editor, "\t\t\t\t\t\t");
+        assert!(app.task_edit.currently_editing == task_edit::CurrentlyEditing::Comments);
+        press(&mut app, handle_task_editor, "j\n");
+        assert!(app.show_task_editor);
+        assert_eq!(app.outbox.pending_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn deletes_a_task() {
+        let backend = MemoryBackend::demo();
+        let mut app = load(&backend).await;
+        press(&mut app, handle_tasks, "jjd");
+        assert_eq!(app.tasks.tasks.len(), 1);
+
+        send_outbox(&app, &backend).await;
+        assert_eq!(contents(&backend).await, ["Try out the todoist TUI"]);
+    }
+
+    #[tokio::test]
+    async fn reloads_when_a_change_is_discarded() {
+        let backend = MemoryBackend::demo();
+        let mut app = load(&backend).await;
+        let task_id = app.tasks.tasks[0].id.clone();
+        // deleted elsewhere, after it was loaded
+        backend.delete_task(task_id).await.unwrap();
+        press(&mut app, handle_tasks, "j+");
+
+        let results = send_outbox(&app, &backend).await;
+        assert!(results
+            .iter()
+            .any(|result| matches!(result, TaskResult::Discarded)));
     }
-    client_key
 }
@@ -1,5 +1,14 @@
+use std::collections::HashMap;
+
 use serde::{Serialize, Serializer};
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{
+    labels,
+    projects::{Collaborator, Project},
+    quick_add::{self, QuickAdd, Resolved},
+    sections::Section,
+};
 
 #[derive(Debug, Default, Serialize)]
 pub struct NewTask<'a> {
@@ -11,7 +20,8 @@ pub struct NewTask<'a> {
     pub section_id: Option<String>,
     pub parent_id: Option<String>,
     pub order: Option<usize>,
-    pub labels: Option<Vec<String>>,
+    #[serde(serialize_with = "serialize_labels")]
+    pub labels: TextArea<'a>,
     pub priority: Option<u8>,
     #[serde(serialize_with = "serialize_text_area")]
     pub due_string: TextArea<'a>,
@@ -19,10 +29,18 @@ pub struct NewTask<'a> {
     pub due_datetime: Option<String>,
     pub due_lang: Option<String>,
     pub assignee_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline_date: Option<String>,
     pub duration: Option<usize>,
     pub duration_unit: Option<DurationUnit>,
     #[serde(skip)]
     pub currently_editing: CurrentlyEditing,
+    /// Sections of the project the task can go in.
+    #[serde(skip)]
+    pub sections: Vec<Section>,
+    /// The line typed in quick add, the full form is shown when `None`.
+    #[serde(skip)]
+    pub quick_add: Option<TextArea<'a>>,
 }
 
 #[derive(Debug, Serialize, Default, PartialEq)]
@@ -31,16 +49,26 @@ pub enum CurrentlyEditing {
     Content,
     Description,
     DueString,
+    Labels,
+    Priority,
+    Section,
 }
 
 fn serialize_text_area<S>(text: &TextArea, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    let s = format!("{}", text.lines().join("\n")); // Convert the TextArea to a String
+    let s = text.lines().join("\n"); // Convert the TextArea to a String
     serializer.serialize_str(&s)
 }
 
+fn serialize_labels<S>(text: &TextArea, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    labels::parse_labels(&text.lines().join(",")).serialize(serializer)
+}
+
 #[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DurationUnit {
@@ -58,6 +86,111 @@ impl<'a> NewTask<'a> {
         }
     }
 
+    /// The whole task on one line, see `QuickAdd`.
+    pub fn new_quick_add(project_id: String) -> Self {
+        NewTask {
+            quick_add: Some(TextArea::default()),
+            ..NewTask::new(project_id, None)
+        }
+    }
+
+    /// What the quick add line says so far. `collaborators` are the people
+    /// in shared projects by project id, `None` while being fetched.
+    pub fn parse_quick_add(
+        &self,
+        projects: &[Project],
+        sections: &[Section],
+        collaborators: &HashMap<String, Option<Vec<Collaborator>>>,
+    ) -> Option<(QuickAdd, Resolved)> {
+        let line = self.quick_add.as_ref()?.lines().join(" ");
+        let quick_add = QuickAdd::parse(&line, projects, sections);
+        let project_id = match &quick_add.project {
+            Some(name) => quick_add::find_project(projects, name).map(|project| &project.id),
+            None => self.project_id.as_ref(),
+        };
+        let collaborators = project_id
+            .and_then(|id| collaborators.get(id))
+            .and_then(|collaborators| collaborators.as_deref());
+        let resolved = quick_add.resolve(
+            projects,
+            sections,
+            collaborators,
+            self.project_id.as_deref(),
+        );
+        Some((quick_add, resolved))
+    }
+
+    /// Fills the form with what was understood and leaves quick add.
+    pub fn apply_quick_add(
+        &mut self,
+        quick_add: &QuickAdd,
+        resolved: &Resolved,
+        sections: &[Section],
+    ) {
+        self.content = TextArea::from(vec![quick_add.content.clone()]);
+        self.content.move_cursor(CursorMove::End);
+        if let Some(project_id) = &resolved.project_id {
+            self.project_id = Some(project_id.clone());
+        }
+        self.set_sections(sections, resolved.section_id.clone());
+        if !quick_add.labels.is_empty() {
+            self.set_labels(&quick_add.labels);
+        }
+        if quick_add.priority.is_some() {
+            self.priority = quick_add.priority;
+        }
+        if let Some(due_string) = &quick_add.due_string {
+            self.due_string = TextArea::from(vec![due_string.clone()]);
+            self.due_string.move_cursor(CursorMove::End);
+        }
+        self.assignee_id = resolved
+            .assignee
+            .as_ref()
+            .map(|assignee| assignee.id.clone());
+        self.deadline_date = resolved.deadline.map(|deadline| deadline.to_string());
+        self.quick_add = None;
+    }
+
+    /// Starts the task off with these labels.
+    pub fn set_labels(&mut self, labels: &[String]) {
+        self.labels = TextArea::from(vec![labels::format_labels(labels)]);
+        self.labels.move_cursor(CursorMove::End);
+    }
+
+    /// P4, the API's 1, until one is picked.
+    pub fn priority(&self) -> u8 {
+        self.priority.unwrap_or(1)
+    }
+
+    /// Offers the project's sections, starting at `section_id`.
+    pub fn set_sections(&mut self, sections: &[Section], section_id: Option<String>) {
+        self.sections = sections
+            .iter()
+            .filter(|section| Some(&section.project_id) == self.project_id.as_ref())
+            .cloned()
+            .collect();
+        self.section_id =
+            section_id.filter(|id| self.sections.iter().any(|section| section.id == *id));
+    }
+
+    /// Steps through the sections, with no section before the first one.
+    pub fn cycle_section(&mut self, forward: bool) {
+        let position = self
+            .section_id
+            .as_ref()
+            .and_then(|id| self.sections.iter().position(|section| section.id == *id))
+            .map_or(0, |position| position + 1);
+        let len = self.sections.len() + 1;
+        let position = if forward {
+            (position + 1) % len
+        } else {
+            (position + len - 1) % len
+        };
+        self.section_id = position
+            .checked_sub(1)
+            .map(|index| self.sections[index].id.clone());
+    }
+
     pub fn get_json(&self) -> serde_json::Value {
         let task_string = serde_json::to_string(self).unwrap();
         serde_json::from_str(&task_string).unwrap()
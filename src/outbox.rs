@@ -0,0 +1,807 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    fs::{self, File},
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::{
+    backend::TodoistBackend,
+    comments::{Comment, CommentTarget},
+    config,
+    error::ApiError,
+    projects::{self, Project},
+    sections::Section,
+    tasks::{self, Task},
+    TaskResult,
+};
+
+/// Longest we wait between two attempts at the same change.
+const MAX_BACKOFF_SECONDS: u64 = 300;
+
+/// Failed attempts after which a change is given up on, so it doesn't hold
+/// up the ones queued behind it forever. Being offline doesn't count.
+const MAX_ATTEMPTS: u32 = 10;
+
+/// A change made locally that Todoist hasn't acknowledged yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Mutation {
+    /// `task` is what we show until Todoist answers, its id is a temporary one.
+    Create {
+        task: Box<Task>,
+        json: serde_json::Value,
+    },
+    Update {
+        task: Box<Task>,
+        json: serde_json::Value,
+    },
+    /// `task` already has its new section.
+    Move {
+        task: Box<Task>,
+    },
+    Close {
+        task_id: String,
+    },
+    Delete {
+        task_id: String,
+    },
+    /// `project` is what we show until Todoist answers, its id is a temporary one.
+    CreateProject {
+        project: Box<Project>,
+        json: serde_json::Value,
+    },
+    UpdateProject {
+        project: Box<Project>,
+        json: serde_json::Value,
+    },
+    MoveProject {
+        project_id: String,
+        parent_id: Option<String>,
+    },
+    ArchiveProject {
+        project_id: String,
+    },
+    DeleteProject {
+        project_id: String,
+    },
+    /// `section` is what we show until Todoist answers, its id is a temporary one.
+    CreateSection {
+        section: Section,
+        json: serde_json::Value,
+    },
+    UpdateSection {
+        section: Section,
+        json: serde_json::Value,
+    },
+    /// New `(id, order)` of every section of a project.
+    ReorderSections {
+        sections: Vec<(String, i32)>,
+    },
+    DeleteSection {
+        section_id: String,
+    },
+    /// `comment` is what we show until Todoist answers, its id is a temporary one.
+    CreateComment {
+        comment: Comment,
+        json: serde_json::Value,
+    },
+    UpdateComment {
+        comment: Comment,
+        json: serde_json::Value,
+    },
+    DeleteComment {
+        comment: Comment,
+    },
+}
+
+/// What Todoist answered to a mutation.
+enum Outcome {
+    Task(Box<Task>),
+    Project(Box<Project>),
+    Section(Section),
+    Comment(Comment),
+    Done,
+}
+
+impl Mutation {
+    fn describe(&self) -> String {
+        match self {
+            Mutation::Create { task, .. } => format!("create \"{}\"", task.content),
+            Mutation::Update { task, .. } => format!("update \"{}\"", task.content),
+            Mutation::Move { task } => format!("move \"{}\"", task.content),
+            Mutation::Close { task_id } => format!("complete task {}", task_id),
+            Mutation::Delete { task_id } => format!("delete task {}", task_id),
+            Mutation::CreateProject { project, .. } => {
+                format!("create project \"{}\"", project.name)
+            }
+            Mutation::UpdateProject { project, .. } => {
+                format!("update project \"{}\"", project.name)
+            }
+            Mutation::MoveProject { project_id, .. } => format!("move project {}", project_id),
+            Mutation::ArchiveProject { project_id } => format!("archive project {}", project_id),
+            Mutation::DeleteProject { project_id } => format!("delete project {}", project_id),
+            Mutation::CreateSection { section, .. } => {
+                format!("create section \"{}\"", section.name)
+            }
+            Mutation::UpdateSection { section, .. } => {
+                format!("rename section \"{}\"", section.name)
+            }
+            Mutation::ReorderSections { .. } => "reorder sections".to_string(),
+            Mutation::DeleteSection { section_id } => format!("delete section {}", section_id),
+            Mutation::CreateComment { .. } => "add a comment".to_string(),
+            Mutation::UpdateComment { .. } => "edit a comment".to_string(),
+            Mutation::DeleteComment { .. } => "delete a comment".to_string(),
+        }
+    }
+
+    /// The temporary id of whatever the mutation creates.
+    fn temp_id(&self) -> Option<&str> {
+        match self {
+            Mutation::Create { task, .. } => Some(&task.id),
+            Mutation::CreateProject { project, .. } => Some(&project.id),
+            Mutation::CreateSection { section, .. } => Some(&section.id),
+            Mutation::CreateComment { comment, .. } => Some(&comment.id),
+            _ => None,
+        }
+    }
+
+    /// Points the mutation at the id Todoist gave something we created
+    /// offline. Temporary ids are unique, so any field holding one is updated.
+    fn replace_id(&mut self, temp_id: &str, id: &str) {
+        self.visit_ids(&mut |value| {
+            if value == temp_id {
+                *value = id.to_string();
+            }
+        });
+    }
+
+    /// Whether any field of the mutation holds the id.
+    fn references(&self, id: &str) -> bool {
+        let mut found = false;
+        self.clone().visit_ids(&mut |value| found |= value == id);
+        found
+    }
+
+    /// Calls `visit` with every id the mutation holds, its own and those of
+    /// the parent, project, section or task it points at.
+    fn visit_ids(&mut self, visit: &mut dyn FnMut(&mut String)) {
+        let visit_json = |json: &mut serde_json::Value, visit: &mut dyn FnMut(&mut String)| {
+            for key in ["id", "parent_id", "project_id", "section_id", "task_id"] {
+                if let Some(serde_json::Value::String(value)) = json.get_mut(key) {
+                    visit(value);
+                }
+            }
+        };
+        let visit_comment = |comment: &mut Comment, visit: &mut dyn FnMut(&mut String)| {
+            visit(&mut comment.id);
+            for value in [comment.task_id.as_mut(), comment.project_id.as_mut()]
+                .into_iter()
+                .flatten()
+            {
+                visit(value);
+            }
+        };
+
+        match self {
+            Mutation::Create { task, json } | Mutation::Update { task, json } => {
+                visit(&mut task.id);
+                visit(&mut task.project_id);
+                for value in [task.parent_id.as_mut(), task.section_id.as_mut()]
+                    .into_iter()
+                    .flatten()
+                {
+                    visit(value);
+                }
+                visit_json(json, visit);
+            }
+            Mutation::Move { task } => {
+                visit(&mut task.id);
+                visit(&mut task.project_id);
+                if let Some(section_id) = task.section_id.as_mut() {
+                    visit(section_id);
+                }
+            }
+            Mutation::CreateProject { project, json }
+            | Mutation::UpdateProject { project, json } => {
+                visit(&mut project.id);
+                if let Some(parent_id) = project.parent_id.as_mut() {
+                    visit(parent_id);
+                }
+                visit_json(json, visit);
+            }
+            Mutation::MoveProject {
+                project_id,
+                parent_id,
+            } => {
+                visit(project_id);
+                if let Some(parent_id) = parent_id.as_mut() {
+                    visit(parent_id);
+                }
+            }
+            Mutation::Close { task_id } | Mutation::Delete { task_id } => visit(task_id),
+            Mutation::ArchiveProject { project_id } | Mutation::DeleteProject { project_id } => {
+                visit(project_id)
+            }
+            Mutation::CreateSection { section, json }
+            | Mutation::UpdateSection { section, json } => {
+                visit(&mut section.id);
+                visit(&mut section.project_id);
+                visit_json(json, visit);
+            }
+            Mutation::ReorderSections { sections } => {
+                for (section_id, _) in sections {
+                    visit(section_id);
+                }
+            }
+            Mutation::DeleteSection { section_id } => visit(section_id),
+            Mutation::CreateComment { comment, json }
+            | Mutation::UpdateComment { comment, json } => {
+                visit_comment(comment, visit);
+                visit_json(json, visit);
+            }
+            Mutation::DeleteComment { comment } => visit_comment(comment, visit),
+        }
+    }
+
+    /// What tells the interface to forget something created offline.
+    fn removed(&self) -> Option<TaskResult> {
+        match self {
+            Mutation::Create { task, .. } => Some(TaskResult::Removed(task.id.clone())),
+            Mutation::CreateProject { project, .. } => {
+                Some(TaskResult::ProjectRemoved(project.id.clone()))
+            }
+            Mutation::CreateSection { section, .. } => {
+                Some(TaskResult::SectionRemoved(section.id.clone()))
+            }
+            Mutation::CreateComment { comment, .. } => {
+                Some(TaskResult::CommentRemoved(comment.id.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Replays the mutation on data fetched before Todoist has seen it.
+    pub fn apply(
+        &self,
+        projects: &mut Vec<Project>,
+        tasks: &mut Vec<Task>,
+        sections: &mut Vec<Section>,
+    ) {
+        match self {
+            Mutation::Create { task, .. } => {
+                if !tasks.iter().any(|existing| existing.id == task.id) {
+                    tasks.push((**task).clone());
+                }
+            }
+            Mutation::Update { task, .. } | Mutation::Move { task } => {
+                if let Some(existing) = tasks.iter_mut().find(|existing| existing.id == task.id) {
+                    *existing = (**task).clone();
+                }
+            }
+            Mutation::Close { task_id } | Mutation::Delete { task_id } => {
+                tasks::remove_task(tasks, task_id)
+            }
+            Mutation::CreateProject { project, .. } => {
+                if !projects.iter().any(|existing| existing.id == project.id) {
+                    projects.push((**project).clone());
+                }
+            }
+            Mutation::UpdateProject { project, .. } => {
+                if let Some(existing) = projects
+                    .iter_mut()
+                    .find(|existing| existing.id == project.id)
+                {
+                    // the parent changes through `MoveProject`
+                    let parent_id = existing.parent_id.take();
+                    *existing = (**project).clone();
+                    existing.parent_id = parent_id;
+                }
+            }
+            Mutation::MoveProject {
+                project_id,
+                parent_id,
+            } => {
+                if let Some(existing) = projects
+                    .iter_mut()
+                    .find(|existing| existing.id == *project_id)
+                {
+                    existing.parent_id = parent_id.clone();
+                }
+            }
+            Mutation::ArchiveProject { project_id } | Mutation::DeleteProject { project_id } => {
+                projects::remove_project(projects, tasks, project_id);
+                sections.retain(|section| projects.iter().any(|p| p.id == section.project_id));
+            }
+            Mutation::CreateSection { section, .. } => {
+                if !sections.iter().any(|existing| existing.id == section.id) {
+                    sections.push(section.clone());
+                }
+            }
+            Mutation::UpdateSection { section, .. } => {
+                if let Some(existing) = sections
+                    .iter_mut()
+                    .find(|existing| existing.id == section.id)
+                {
+                    existing.name = section.name.clone();
+                }
+            }
+            Mutation::ReorderSections { sections: orders } => {
+                for (id, order) in orders {
+                    if let Some(existing) = sections.iter_mut().find(|existing| existing.id == *id)
+                    {
+                        existing.order = *order;
+                    }
+                }
+            }
+            Mutation::DeleteSection { section_id } => {
+                sections.retain(|section| section.id != *section_id);
+                tasks.retain(|task| task.section_id.as_ref() != Some(section_id));
+            }
+            // comments are fetched on their own, see `apply_to_comments`
+            Mutation::CreateComment { .. }
+            | Mutation::UpdateComment { .. }
+            | Mutation::DeleteComment { .. } => {}
+        }
+    }
+
+    /// Replays the mutation on the comments of `target` fetched before
+    /// Todoist has seen it.
+    pub fn apply_to_comments(&self, target: &CommentTarget, comments: &mut Vec<Comment>) {
+        match self {
+            Mutation::CreateComment { comment, .. }
+                if comment.target().as_ref() == Some(target)
+                    && !comments.iter().any(|existing| existing.id == comment.id) =>
+            {
+                comments.push(comment.clone());
+            }
+            Mutation::UpdateComment { comment, .. } => {
+                if let Some(existing) = comments
+                    .iter_mut()
+                    .find(|existing| existing.id == comment.id)
+                {
+                    existing.content = comment.content.clone();
+                }
+            }
+            Mutation::DeleteComment { comment } => {
+                comments.retain(|existing| existing.id != comment.id)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Ids for tasks created locally, replaced once Todoist has created them.
+pub fn temp_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "local-{}-{}",
+        Local::now().timestamp_millis(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Whether the id is one of `temp_id`'s, for something Todoist hasn't seen.
+pub fn is_temp_id(id: &str) -> bool {
+    id.starts_with("local-")
+}
+
+/// Persistent queue of mutations, replayed in order by `run`.
+#[derive(Debug, Clone, Default)]
+pub struct Outbox {
+    pending: Arc<Mutex<VecDeque<Mutation>>>,
+    /// The ids Todoist gave what was created this session, by temporary id,
+    /// for changes queued before the interface heard about them.
+    created: Arc<Mutex<HashMap<String, String>>>,
+    notify: Arc<Notify>,
+    path: Option<PathBuf>,
+}
+
+impl Outbox {
+    pub fn default_path() -> PathBuf {
+        config::config_dir_path().join("outbox.json")
+    }
+
+    /// Loads whatever was left over from the last session. Without a path
+    /// nothing is persisted.
+    ///
+    /// A file that can't be read comes back as an error to show, next to an
+    /// empty outbox. One that doesn't parse is moved aside to `.bad` first,
+    /// otherwise nothing is persisted so the file is left alone.
+    pub fn load(mut path: Option<PathBuf>) -> (Outbox, Option<String>) {
+        let (pending, error) = match path.as_deref().map(read) {
+            None => (VecDeque::new(), None),
+            Some(Ok(pending)) => (pending, None),
+            Some(Err(Unreadable::Io(e))) if e.kind() == io::ErrorKind::NotFound => {
+                (VecDeque::new(), None)
+            }
+            Some(Err(e)) => {
+                let from = path.take().unwrap_or_default();
+                let mut message = format!(
+                    "The changes waiting to be sent in {} could not be read: {}",
+                    from.display(),
+                    e
+                );
+                let moved = match e {
+                    Unreadable::Json(_) => {
+                        let mut to = from.clone().into_os_string();
+                        to.push(".bad");
+                        let to = PathBuf::from(to);
+                        fs::rename(&from, &to).ok().map(|()| to)
+                    }
+                    Unreadable::Io(_) => None,
+                };
+                match moved {
+                    Some(to) => {
+                        message.push_str(&format!("\n\nIt was moved to {}.", to.display()));
+                        path = Some(from);
+                    }
+                    None => message.push_str("\n\nNew changes won't be saved until it is fixed."),
+                }
+                (VecDeque::new(), Some(message))
+            }
+        };
+
+        let outbox = Outbox {
+            pending: Arc::new(Mutex::new(pending)),
+            created: Arc::default(),
+            notify: Arc::new(Notify::new()),
+            path,
+        };
+        (outbox, error)
+    }
+
+    pub fn push(&self, mut mutation: Mutation) {
+        let mut pending = self.pending.lock().unwrap();
+        let created = self.created.lock().unwrap();
+        mutation.visit_ids(&mut |value| {
+            if let Some(id) = created.get(value) {
+                *value = id.clone();
+            }
+        });
+        pending.push_back(mutation);
+        self.save(&pending);
+        self.notify.notify_one();
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    pub fn pending(&self) -> Vec<Mutation> {
+        self.pending.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn front(&self) -> Option<Mutation> {
+        self.pending.lock().unwrap().front().cloned()
+    }
+
+    fn pop_front(&self, created: Option<(&str, &str)>) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.pop_front();
+        if let Some((temp_id, id)) = created {
+            for mutation in pending.iter_mut() {
+                mutation.replace_id(temp_id, id);
+            }
+            self.created
+                .lock()
+                .unwrap()
+                .insert(temp_id.to_string(), id.to_string());
+        }
+        self.save(&pending);
+    }
+
+    /// Drops the queued mutations pointing at something whose creation
+    /// failed, and in turn those pointing at what they would have created,
+    /// e.g. the subtasks of a task.
+    fn drop_dependents(&self, temp_id: &str) -> Vec<Mutation> {
+        let mut pending = self.pending.lock().unwrap();
+        let mut gone = vec![temp_id.to_string()];
+        let mut dropped = Vec::new();
+        while let Some(index) = pending
+            .iter()
+            .position(|mutation| gone.iter().any(|id| mutation.references(id)))
+        {
+            let Some(mutation) = pending.remove(index) else {
+                break;
+            };
+            if let Some(id) = mutation.temp_id() {
+                gone.push(id.to_string());
+            }
+            dropped.push(mutation);
+        }
+        self.save(&pending);
+        dropped
+    }
+
+    fn save(&self, pending: &VecDeque<Mutation>) {
+        if let Some(path) = &self.path {
+            let _ = write(path, pending);
+        }
+    }
+}
+
+/// Why `read` failed.
+#[derive(Debug)]
+enum Unreadable {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for Unreadable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unreadable::Io(e) => e.fmt(f),
+            Unreadable::Json(e) => e.fmt(f),
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<VecDeque<Mutation>, Unreadable> {
+    let file = File::open(path).map_err(Unreadable::Io)?;
+    serde_json::from_reader(BufReader::new(file)).map_err(Unreadable::Json)
+}
+
+fn write(path: &Path, pending: &VecDeque<Mutation>) -> std::io::Result<()> {
+    let json = serde_json::to_string(pending)?;
+    config::write_atomically(path, &json)
+}
+
+/// Sends queued mutations to Todoist one at a time, forever. Transient
+/// failures are retried with exponential backoff up to `MAX_ATTEMPTS`,
+/// anything else is dropped and reported as a conflict, along with what
+/// was queued on top of it.
+pub async fn run<B: TodoistBackend>(outbox: Outbox, backend: B, tx: Sender<TaskResult>) {
+    let mut attempt: u32 = 0;
+    // attempts that failed for some other reason than being offline
+    let mut failures: u32 = 0;
+
+    loop {
+        let Some(mutation) = outbox.front() else {
+            outbox.notify.notified().await;
+            continue;
+        };
+
+        match send(&backend, &mutation).await {
+            Ok(outcome) => {
+                attempt = 0;
+                failures = 0;
+                let created_id = match &outcome {
+                    Outcome::Task(task) => Some(task.id.clone()),
+                    Outcome::Project(project) => Some(project.id.clone()),
+                    Outcome::Section(section) => Some(section.id.clone()),
+                    Outcome::Comment(comment) => Some(comment.id.clone()),
+                    Outcome::Done => None,
+                };
+                let created = mutation.temp_id().zip(created_id.as_deref());
+                outbox.pop_front(created);
+
+                let message = match (mutation.temp_id(), outcome) {
+                    (Some(temp_id), Outcome::Task(task)) => Some(TaskResult::Created {
+                        temp_id: temp_id.to_string(),
+                        task,
+                    }),
+                    (Some(temp_id), Outcome::Project(project)) => {
+                        Some(TaskResult::ProjectCreated {
+                            temp_id: temp_id.to_string(),
+                            project,
+                        })
+                    }
+                    (None, Outcome::Task(task)) => Some(TaskResult::Task(task)),
+                    (None, Outcome::Project(project)) => Some(TaskResult::Project(project)),
+                    (Some(temp_id), Outcome::Section(section)) => {
+                        Some(TaskResult::SectionCreated {
+                            temp_id: temp_id.to_string(),
+                            section,
+                        })
+                    }
+                    (None, Outcome::Section(section)) => Some(TaskResult::Section(section)),
+                    (Some(temp_id), Outcome::Comment(comment)) => {
+                        Some(TaskResult::CommentCreated {
+                            temp_id: temp_id.to_string(),
+                            comment,
+                        })
+                    }
+                    (None, Outcome::Comment(comment)) => Some(TaskResult::Comment(comment)),
+                    (_, Outcome::Done) => None,
+                };
+                if let Some(message) = message {
+                    let _ = tx.send(message);
+                }
+            }
+            Err(e) if e.is_transient() && failures + 1 < MAX_ATTEMPTS => {
+                attempt += 1;
+                if !matches!(e, ApiError::Network(_)) {
+                    failures += 1;
+                }
+                // A bad token won't fix itself, say so instead of retrying silently.
+                if attempt == 1 && matches!(e, ApiError::Unauthorized(_)) {
+                    let _ = tx.send(TaskResult::Error(e.to_string()));
+                }
+                let seconds = match e {
+                    ApiError::RateLimited {
+                        retry_after: Some(seconds),
+                    } => seconds,
+                    _ => 2u64.saturating_pow(attempt).min(MAX_BACKOFF_SECONDS),
+                };
+                tokio::time::sleep(Duration::from_secs(seconds)).await;
+            }
+            Err(e) => {
+                let gave_up = e.is_transient();
+                attempt = 0;
+                failures = 0;
+                outbox.pop_front(None);
+                let dropped = match mutation.temp_id() {
+                    Some(temp_id) => outbox.drop_dependents(temp_id),
+                    None => Vec::new(),
+                };
+                let mut discarded = false;
+                for mutation in std::iter::once(&mutation).chain(&dropped) {
+                    match mutation.removed() {
+                        Some(removed) => {
+                            let _ = tx.send(removed);
+                        }
+                        None => discarded = true,
+                    }
+                }
+                if discarded {
+                    let _ = tx.send(TaskResult::Discarded);
+                }
+
+                let mut message = if gave_up {
+                    format!(
+                        "Could not {} after {} attempts, the change was discarded.",
+                        mutation.describe(),
+                        MAX_ATTEMPTS
+                    )
+                } else {
+                    format!(
+                        "Could not {}, the change was discarded.",
+                        mutation.describe()
+                    )
+                };
+                match dropped.len() {
+                    0 => {}
+                    1 => message.push_str(" So was 1 change that depended on it."),
+                    n => message.push_str(&format!(" So were {} changes that depended on it.", n)),
+                }
+                let _ = tx.send(TaskResult::Error(format!("{}\n\n{}", message, e)));
+            }
+        }
+    }
+}
+
+async fn send<B: TodoistBackend>(backend: &B, mutation: &Mutation) -> Result<Outcome, ApiError> {
+    match mutation {
+        Mutation::Create { json, .. } => backend
+            .create_task(json.clone())
+            .await
+            .map(|task| Outcome::Task(Box::new(task))),
+        Mutation::Update { task, json } => backend
+            .update_task(task.id.clone(), json.clone())
+            .await
+            .map(|task| Outcome::Task(Box::new(task))),
+        Mutation::Move { task } => backend
+            .move_task(
+                task.id.clone(),
+                task.project_id.clone(),
+                task.section_id.clone(),
+            )
+            .await
+            .map(|_| Outcome::Done),
+        Mutation::Close { task_id } => backend
+            .close_task(task_id.clone())
+            .await
+            .map(|_| Outcome::Done),
+        Mutation::Delete { task_id } => backend
+            .delete_task(task_id.clone())
+            .await
+            .map(|_| Outcome::Done),
+        Mutation::CreateProject { json, .. } => backend
+            .create_project(json.clone())
+            .await
+            .map(|project| Outcome::Project(Box::new(project))),
+        Mutation::UpdateProject { project, json } => backend
+            .update_project(project.id.clone(), json.clone())
+            .await
+            .map(|project| Outcome::Project(Box::new(project))),
+        Mutation::MoveProject {
+            project_id,
+            parent_id,
+        } => backend
+            .move_project(project_id.clone(), parent_id.clone())
+            .await
+            .map(|_| Outcome::Done),
+        Mutation::ArchiveProject { project_id } => backend
+            .archive_project(project_id.clone())
+            .await
+            .map(|_| Outcome::Done),
+        Mutation::DeleteProject { project_id } => backend
+            .delete_project(project_id.clone())
+            .await
+            .map(|_| Outcome::Done),
+        Mutation::CreateSection { json, .. } => backend
+            .create_section(json.clone())
+            .await
+            .map(Outcome::Section),
+        Mutation::UpdateSection { section, json } => backend
+            .update_section(section.id.clone(), json.clone())
+            .await
+            .map(Outcome::Section),
+        Mutation::ReorderSections { sections } => backend
+            .reorder_sections(sections.clone())
+            .await
+            .map(|_| Outcome::Done),
+        Mutation::DeleteSection { section_id } => backend
+            .delete_section(section_id.clone())
+            .await
+            .map(|_| Outcome::Done),
+        Mutation::CreateComment { json, .. } => backend
+            .create_comment(json.clone())
+            .await
+            .map(Outcome::Comment),
+        Mutation::UpdateComment { comment, json } => backend
+            .update_comment(comment.id.clone(), json.clone())
+            .await
+            .map(Outcome::Comment),
+        Mutation::DeleteComment { comment } => backend
+            .delete_comment(comment.id.clone())
+            .await
+            .map(|_| Outcome::Done),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreadable_outbox_is_moved_aside() {
+        let dir = std::env::temp_dir().join(format!("todoist-outbox-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("outbox.json");
+
+        let (outbox, error) = Outbox::load(Some(path.clone()));
+        assert_eq!((outbox.pending_count(), error), (0, None));
+
+        fs::write(&path, "[{\"kind\": ").unwrap();
+        let (outbox, error) = Outbox::load(Some(path.clone()));
+        assert_eq!(outbox.pending_count(), 0);
+        assert!(error.is_some());
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("outbox.json.bad")).unwrap(),
+            "[{\"kind\": "
+        );
+
+        outbox.push(Mutation::Close {
+            task_id: "1".to_string(),
+        });
+        assert_eq!(read(&path).unwrap().len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn changes_queued_after_a_create_get_its_id() {
+        let outbox = Outbox::default();
+        let close = |task_id: &str| Mutation::Close {
+            task_id: task_id.to_string(),
+        };
+        // Todoist created "local-1" as "42"
+        outbox.pop_front(Some(("local-1", "42")));
+        // the interface hasn't heard about "42" yet
+        outbox.push(close("local-1"));
+        assert!(outbox.pending()[0].references("42"));
+    }
+}
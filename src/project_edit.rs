@@ -0,0 +1,177 @@
+use ratatui::style::{Color, Modifier, Style};
+use serde_json::json;
+use tui_textarea::TextArea;
+
+use crate::projects::{ListType, Project, Projects};
+
+/// The colors Todoist offers for projects, with what they look like.
+pub const COLORS: [(&str, Color); 20] = [
+    ("berry_red", Color::Rgb(0xb8, 0x25, 0x6f)),
+    ("red", Color::Rgb(0xdb, 0x40, 0x35)),
+    ("orange", Color::Rgb(0xff, 0x99, 0x33)),
+    ("yellow", Color::Rgb(0xfa, 0xd0, 0x00)),
+    ("olive_green", Color::Rgb(0xaf, 0xb8, 0x3b)),
+    ("lime_green", Color::Rgb(0x7e, 0xcc, 0x49)),
+    ("green", Color::Rgb(0x29, 0x94, 0x38)),
+    ("mint_green", Color::Rgb(0x6a, 0xcc, 0xbc)),
+    ("teal", Color::Rgb(0x15, 0x8f, 0xad)),
+    ("sky_blue", Color::Rgb(0x14, 0xaa, 0xf5)),
+    ("light_blue", Color::Rgb(0x96, 0xc3, 0xeb)),
+    ("blue", Color::Rgb(0x40, 0x73, 0xff)),
+    ("grape", Color::Rgb(0x88, 0x4d, 0xff)),
+    ("violet", Color::Rgb(0xaf, 0x38, 0xeb)),
+    ("lavender", Color::Rgb(0xeb, 0x96, 0xeb)),
+    ("magenta", Color::Rgb(0xe0, 0x51, 0x94)),
+    ("salmon", Color::Rgb(0xff, 0x8d, 0x85)),
+    ("charcoal", Color::Rgb(0x80, 0x80, 0x80)),
+    ("grey", Color::Rgb(0xb8, 0xb8, 0xb8)),
+    ("taupe", Color::Rgb(0xcc, 0xac, 0x93)),
+];
+
+#[derive(Debug, Default, Clone)]
+pub struct ProjectEdit<'a> {
+    /// `None` when creating a new project.
+    pub project_id: Option<String>,
+    pub name: TextArea<'a>,
+    /// Index into `COLORS`.
+    pub color: usize,
+    /// Projects this one can be nested under, as `(id, name)`. `None` is the
+    /// top level.
+    pub parents: Vec<(Option<String>, String)>,
+    /// Index into `parents`.
+    pub parent: usize,
+    pub is_favorite: bool,
+    pub view_style: ListType,
+    pub currently_editing: CurrentlyEditing,
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+pub enum CurrentlyEditing {
+    #[default]
+    Name,
+    Color,
+    Parent,
+    Favorite,
+    ViewStyle,
+}
+
+impl<'a> ProjectEdit<'a> {
+    /// Opens the editor on `project`, or on a blank project nested under
+    /// `parent_id`.
+    pub fn new(projects: &Projects, project: Option<&Project>, parent_id: Option<&str>) -> Self {
+        // a project can't be nested under itself or its own sub-projects
+        let excluded = project
+            .map(|project| {
+                let mut excluded = projects.descendants(&project.id);
+                excluded.insert(project.id.clone());
+                excluded
+            })
+            .unwrap_or_default();
+
+        let mut parents = vec![(None, "None".to_string())];
+        for index in &projects.display_projects {
+            let candidate = &projects.projects[*index];
+            if !excluded.contains(&candidate.id) && !candidate.is_inbox_project {
+                let indent = "  ".repeat(projects.depth(candidate));
+                parents.push((
+                    Some(candidate.id.clone()),
+                    format!("{}{}", indent, candidate.name),
+                ));
+            }
+        }
+
+        let parent_id = project.map_or(parent_id, |project| project.parent_id.as_deref());
+        let parent = parents
+            .iter()
+            .position(|(id, _)| id.as_deref() == parent_id)
+            .unwrap_or(0);
+
+        let mut project_edit = match project {
+            Some(project) => ProjectEdit {
+                project_id: Some(project.id.clone()),
+                name: TextArea::from(vec![project.name.clone()]),
+                color: COLORS
+                    .iter()
+                    .position(|(name, _)| *name == project.color)
+                    .unwrap_or(0),
+                parents,
+                parent,
+                is_favorite: project.is_favorite,
+                view_style: project.view_style.clone(),
+                currently_editing: CurrentlyEditing::Name,
+            },
+            None => ProjectEdit {
+                color: COLORS
+                    .iter()
+                    .position(|(name, _)| *name == "charcoal")
+                    .unwrap_or(0),
+                parents,
+                parent,
+                ..Default::default()
+            },
+        };
+        project_edit.update_cursor_styles();
+        project_edit
+    }
+
+    pub fn parent_id(&self) -> Option<String> {
+        self.parents.get(self.parent).and_then(|(id, _)| id.clone())
+    }
+
+    pub fn next_field(&mut self) {
+        self.currently_editing = match self.currently_editing {
+            CurrentlyEditing::Name => CurrentlyEditing::Color,
+            CurrentlyEditing::Color => CurrentlyEditing::Parent,
+            CurrentlyEditing::Parent => CurrentlyEditing::Favorite,
+            CurrentlyEditing::Favorite => CurrentlyEditing::ViewStyle,
+            CurrentlyEditing::ViewStyle => CurrentlyEditing::Name,
+        };
+        self.update_cursor_styles();
+    }
+
+    /// Steps the focused choice forwards or backwards.
+    pub fn cycle(&mut self, forward: bool) {
+        let step = |value: usize, len: usize| {
+            if forward {
+                (value + 1) % len
+            } else {
+                (value + len - 1) % len
+            }
+        };
+        match self.currently_editing {
+            CurrentlyEditing::Name => {}
+            CurrentlyEditing::Color => self.color = step(self.color, COLORS.len()),
+            CurrentlyEditing::Parent => self.parent = step(self.parent, self.parents.len()),
+            CurrentlyEditing::Favorite => self.is_favorite = !self.is_favorite,
+            CurrentlyEditing::ViewStyle => {
+                self.view_style = match self.view_style {
+                    ListType::List => ListType::Board,
+                    ListType::Board => ListType::List,
+                }
+            }
+        }
+    }
+
+    pub fn get_json(&self) -> serde_json::Value {
+        let mut json = json!({
+            "name": self.name.lines().join(" "),
+            "color": COLORS[self.color].0,
+            "is_favorite": self.is_favorite,
+            "view_style": self.view_style,
+        });
+        // REST only takes a parent on creation, moves go through `MoveProject`
+        if self.project_id.is_none() {
+            json["parent_id"] = json!(self.parent_id());
+        }
+        json
+    }
+
+    pub fn update_cursor_styles(&mut self) {
+        let style = if self.currently_editing == CurrentlyEditing::Name {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        self.name.set_cursor_style(style);
+    }
+}
@@ -1,14 +1,25 @@
+use std::collections::{HashMap, HashSet};
+
 use ratatui::widgets::ListState;
 use serde::{Deserialize, Serialize};
 
+use crate::{tasks::Task, tree};
+
 #[derive(Debug, Default)]
 pub struct Projects {
     pub projects: Vec<Project>,
     pub state: ListState,
     pub selected_project: Option<String>,
+    /// Indices into `projects` in tree order, without the children of
+    /// collapsed projects. `state` indexes into this.
+    pub display_projects: Vec<usize>,
+    /// Ids of projects whose sub-projects are hidden.
+    pub collapsed: HashSet<String>,
+    /// Whether a project's tasks include those of its sub-projects.
+    pub include_subprojects: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type")]
 pub struct Project {
     pub id: String,
@@ -25,20 +36,137 @@ pub struct Project {
     pub parent_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Someone a shared project's tasks can be assigned to.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Collaborator {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+impl Collaborator {
+    /// Whether `name` is this person's full or first name, or the start of
+    /// their email, ignoring case.
+    pub fn answers_to(&self, name: &str) -> bool {
+        let first_name = self.name.split_whitespace().next().unwrap_or_default();
+        let user = self.email.split('@').next().unwrap_or_default();
+        [self.name.as_str(), first_name, user]
+            .iter()
+            .any(|candidate| candidate.eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ListType {
     Board,
+    #[default]
     List,
 }
 
+impl Project {
+    /// Builds a project locally from the JSON used to create it, for when
+    /// Todoist hasn't answered yet.
+    pub fn from_json(id: String, json: &serde_json::Value) -> Project {
+        let string_field = |key: &str| json[key].as_str().map(|s| s.to_string());
+
+        Project {
+            id,
+            name: string_field("name").unwrap_or_default(),
+            comment_count: 0,
+            order: 0,
+            color: string_field("color").unwrap_or_else(|| "charcoal".to_string()),
+            is_shared: false,
+            is_favorite: json["is_favorite"].as_bool().unwrap_or(false),
+            is_inbox_project: false,
+            is_team_inbox: false,
+            view_style: serde_json::from_value(json["view_style"].clone())
+                .unwrap_or(ListType::List),
+            url: String::new(),
+            parent_id: string_field("parent_id"),
+        }
+    }
+}
+
 impl Projects {
     pub fn new(items: Vec<Project>) -> Projects {
-        Projects {
+        let mut projects = Projects {
             projects: items,
             state: ListState::default(),
             selected_project: None,
+            display_projects: Vec::new(),
+            collapsed: HashSet::new(),
+            include_subprojects: false,
+        };
+        projects.build_tree();
+        projects
+    }
+
+    /// Rebuilds `display_projects`, siblings ordered by `order`. Projects
+    /// whose parent is missing are shown at the top level.
+    pub fn build_tree(&mut self) {
+        let mut children: HashMap<Option<&str>, Vec<usize>> = HashMap::new();
+        for (index, project) in self.projects.iter().enumerate() {
+            let parent = project
+                .parent_id
+                .as_deref()
+                .filter(|parent_id| self.projects.iter().any(|p| p.id == *parent_id));
+            children.entry(parent).or_default().push(index);
+        }
+        for siblings in children.values_mut() {
+            siblings.sort_by_key(|index| self.projects[*index].order);
         }
+
+        let mut display_projects = Vec::new();
+        let mut stack: Vec<usize> = children.get(&None).cloned().unwrap_or_default();
+        stack.reverse();
+        while let Some(index) = stack.pop() {
+            display_projects.push(index);
+            let project = &self.projects[index];
+            if self.collapsed.contains(&project.id) {
+                continue;
+            }
+            if let Some(siblings) = children.get(&Some(project.id.as_str())) {
+                stack.extend(siblings.iter().rev());
+            }
+        }
+        self.display_projects = display_projects;
+    }
+
+    pub fn depth(&self, project: &Project) -> usize {
+        tree::ancestors(&self.projects, project).count()
+    }
+
+    pub fn has_children(&self, id: &str) -> bool {
+        self.projects
+            .iter()
+            .any(|project| project.parent_id.as_deref() == Some(id))
+    }
+
+    /// Ids of every project nested under `id`, at any depth.
+    pub fn descendants(&self, id: &str) -> HashSet<String> {
+        tree::descendants(&self.projects, id)
+    }
+
+    /// Shows or hides the sub-projects of the highlighted project.
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        let Some(id) = self.selected_id() else {
+            return;
+        };
+        if collapsed && self.has_children(&id) {
+            self.collapsed.insert(id.clone());
+        } else {
+            self.collapsed.remove(&id);
+        }
+        self.build_tree();
+        self.select_id(Some(&id));
+    }
+
+    pub fn selected(&self) -> Option<&Project> {
+        self.state
+            .selected()
+            .and_then(|i| self.display_projects.get(i))
+            .and_then(|index| self.projects.get(*index))
     }
 
     // pub async fn initialise(&mut self) {
@@ -46,13 +174,13 @@ impl Projects {
     // }
 
     pub fn next(&mut self) {
-        if self.projects.len() == 0 {
+        if self.display_projects.is_empty() {
             self.state.select(None);
             return;
         }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.projects.len() - 1 {
+                if i >= self.display_projects.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -64,14 +192,14 @@ impl Projects {
     }
 
     pub fn previous(&mut self) {
-        if self.projects.len() == 0 {
+        if self.display_projects.is_empty() {
             self.state.select(None);
             return;
         }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.projects.len() - 1
+                    self.display_projects.len() - 1
                 } else {
                     i - 1
                 }
@@ -81,9 +209,31 @@ impl Projects {
         self.state.select(Some(i));
     }
 
+    pub fn selected_id(&self) -> Option<String> {
+        self.selected().map(|project| project.id.clone())
+    }
+
+    pub fn select_id(&mut self, id: Option<&str>) {
+        let index = id.and_then(|id| {
+            self.display_projects
+                .iter()
+                .position(|index| self.projects[*index].id == id)
+        });
+        self.state.select(index);
+    }
+
     pub fn unselect(&mut self) {
         let offset = self.state.offset();
         self.state.select(None);
         *self.state.offset_mut() = offset;
     }
 }
+
+/// Removes a project along with its sub-projects and all of their tasks, as
+/// Todoist does when one is archived or deleted.
+pub fn remove_project(projects: &mut Vec<Project>, tasks: &mut Vec<Task>, id: &str) {
+    let mut removed = tree::descendants(projects, id);
+    removed.insert(id.to_string());
+    projects.retain(|project| !removed.contains(&project.id));
+    tasks.retain(|task| !removed.contains(&task.project_id));
+}
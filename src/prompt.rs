@@ -0,0 +1,53 @@
+use tui_textarea::TextArea;
+
+use crate::comments::CommentTarget;
+
+/// A single line of text asked for in a modal, e.g. a section's name.
+#[derive(Debug, Clone)]
+pub struct Prompt<'a> {
+    pub title: String,
+    pub input: TextArea<'a>,
+    pub action: PromptAction,
+}
+
+/// What happens with the text once it is entered.
+#[derive(Debug, Clone)]
+pub enum PromptAction {
+    NewSection {
+        project_id: String,
+    },
+    RenameSection {
+        section_id: String,
+    },
+    /// Shows the tasks matching a filter query.
+    Query,
+    NewComment {
+        target: CommentTarget,
+    },
+    EditComment {
+        comment_id: String,
+    },
+    /// Writes the tasks to the file, in the format its extension names.
+    Export {
+        /// Every task instead of the view's.
+        all: bool,
+    },
+    /// Reads the tasks of the file and shows what importing them would add.
+    Import,
+}
+
+impl<'a> Prompt<'a> {
+    pub fn new(title: &str, value: &str, action: PromptAction) -> Prompt<'a> {
+        let mut input = TextArea::from(vec![value.to_string()]);
+        input.move_cursor(tui_textarea::CursorMove::End);
+        Prompt {
+            title: title.to_string(),
+            input,
+            action,
+        }
+    }
+
+    pub fn value(&self) -> String {
+        self.input.lines().join(" ").trim().to_string()
+    }
+}
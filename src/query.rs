@@ -0,0 +1,596 @@
+use std::iter;
+
+use chrono::{Datelike, Local, NaiveDate, TimeDelta};
+
+use crate::{projects::Project, sections::Section, tasks::Task, tree};
+
+/// A parsed Todoist filter query, e.g. `(today | overdue) & #Work`.
+///
+/// `|` and `,` combine terms with or, `&` with and, `!` negates and
+/// parentheses group. A `\` in front of any of those makes it part of a
+/// name instead.
+#[derive(Debug, Clone)]
+pub struct Query {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    All,
+    Overdue,
+    NoDate,
+    DueOn(DateSpec),
+    DueBefore(DateSpec),
+    DueAfter(DateSpec),
+    /// Due within this many days from today, or in the past when negative.
+    DueWithin(i64),
+    Recurring,
+    Subtask,
+    /// As the API counts it, 4 is `p1`.
+    Priority(u8),
+    Project {
+        name: String,
+        with_subprojects: bool,
+    },
+    Section(String),
+    Label(String),
+    NoLabels,
+    Search(String),
+}
+
+/// Relative dates are kept relative, so a query still means the same
+/// thing after midnight.
+#[derive(Debug, Clone)]
+enum DateSpec {
+    DaysFromToday(i64),
+    Date(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Or,
+    And,
+    Not,
+    Open,
+    Close,
+    Term(String),
+}
+
+/// A thousand years, keeps relative dates well within the ones there are.
+const MAX_DAYS: i64 = 366_000;
+
+/// What a query needs to know besides the task itself.
+pub struct Context<'a> {
+    pub today: NaiveDate,
+    pub projects: &'a [Project],
+    pub sections: &'a [Section],
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Query, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text),
+            position: 0,
+        };
+        let expr = parser.parse_or()?;
+        match parser.next() {
+            None => Ok(Query { expr }),
+            Some(token) => Err(format!("unexpected {}", token.describe())),
+        }
+    }
+
+    pub fn matches(&self, task: &Task, context: &Context) -> bool {
+        self.expr.matches(task, context)
+    }
+}
+
+impl Expr {
+    fn matches(&self, task: &Task, context: &Context) -> bool {
+        match self {
+            Expr::Or(left, right) => left.matches(task, context) || right.matches(task, context),
+            Expr::And(left, right) => left.matches(task, context) && right.matches(task, context),
+            Expr::Not(expr) => !expr.matches(task, context),
+            Expr::Term(term) => term.matches(task, context),
+        }
+    }
+}
+
+impl Term {
+    fn matches(&self, task: &Task, context: &Context) -> bool {
+        let today = context.today;
+        let due = task.due.as_ref().map(|due| due.date);
+        match self {
+            Term::All => true,
+            Term::Overdue => due.is_some_and(|due| due < today),
+            Term::NoDate => due.is_none(),
+            Term::DueOn(date) => due.is_some() && due == date.resolve(today),
+            Term::DueBefore(date) => due
+                .zip(date.resolve(today))
+                .is_some_and(|(due, date)| due < date),
+            Term::DueAfter(date) => due
+                .zip(date.resolve(today))
+                .is_some_and(|(due, date)| due > date),
+            Term::DueWithin(days) => {
+                let Some(other) = add_days(today, *days) else {
+                    return false;
+                };
+                due.is_some_and(|due| due >= today.min(other) && due <= today.max(other))
+            }
+            Term::Recurring => task.due.as_ref().is_some_and(|due| due.is_recurring),
+            Term::Subtask => task.parent_id.is_some(),
+            Term::Priority(priority) => task.priority == *priority,
+            Term::Project {
+                name,
+                with_subprojects,
+            } => {
+                let Some(project) = context
+                    .projects
+                    .iter()
+                    .find(|project| project.id == task.project_id)
+                else {
+                    return false;
+                };
+                if *with_subprojects {
+                    iter::once(project)
+                        .chain(tree::ancestors(context.projects, project))
+                        .any(|project| name_matches(name, &project.name))
+                } else {
+                    name_matches(name, &project.name)
+                }
+            }
+            Term::Section(name) => task.section_id.as_ref().is_some_and(|section_id| {
+                context
+                    .sections
+                    .iter()
+                    .any(|section| section.id == *section_id && name_matches(name, &section.name))
+            }),
+            Term::Label(name) => task.labels.iter().any(|label| name_matches(name, label)),
+            Term::NoLabels => task.labels.is_empty(),
+            Term::Search(text) => task.content.to_lowercase().contains(text),
+        }
+    }
+}
+
+impl DateSpec {
+    /// `None` past the dates there are, which parsing already rules out.
+    fn resolve(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            DateSpec::DaysFromToday(days) => add_days(today, *days),
+            DateSpec::Date(date) => Some(*date),
+        }
+    }
+}
+
+fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    date.checked_add_signed(TimeDelta::try_days(days)?)
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Or => "\"|\"".to_string(),
+            Token::And => "\"&\"".to_string(),
+            Token::Not => "\"!\"".to_string(),
+            Token::Open => "\"(\"".to_string(),
+            Token::Close => "\")\"".to_string(),
+            Token::Term(text) => format!("\"{}\"", text),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut term = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        let operator = match c {
+            '|' | ',' => Token::Or,
+            '&' => Token::And,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            // only a negation in front of a term, names may contain it
+            '!' if term.trim().is_empty() => Token::Not,
+            '\\' => {
+                term.extend(chars.next());
+                continue;
+            }
+            _ => {
+                term.push(c);
+                continue;
+            }
+        };
+        push_term(&mut tokens, &mut term);
+        tokens.push(operator);
+    }
+    push_term(&mut tokens, &mut term);
+
+    tokens
+}
+
+fn push_term(tokens: &mut Vec<Token>, term: &mut String) {
+    let text = term.trim();
+    if !text.is_empty() {
+        tokens.push(Token::Term(text.to_string()));
+    }
+    term.clear();
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.position) == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.parse_unary()?))),
+            Some(Token::Open) => {
+                let expr = self.parse_or()?;
+                if !self.eat(&Token::Close) {
+                    return Err("missing \")\"".to_string());
+                }
+                Ok(expr)
+            }
+            Some(Token::Term(text)) => parse_term(&text).map(Expr::Term),
+            Some(token) => Err(format!("unexpected {}", token.describe())),
+            None => Err("the query ends too early".to_string()),
+        }
+    }
+}
+
+fn parse_term(text: &str) -> Result<Term, String> {
+    if let Some(name) = text.strip_prefix("##") {
+        return Ok(Term::Project {
+            name: name.trim().to_string(),
+            with_subprojects: true,
+        });
+    }
+    if let Some(name) = text.strip_prefix('#') {
+        return Ok(Term::Project {
+            name: name.trim().to_string(),
+            with_subprojects: false,
+        });
+    }
+    if let Some(name) = text.strip_prefix('@') {
+        return Ok(Term::Label(name.trim().to_string()));
+    }
+    if let Some(name) = text.strip_prefix('/') {
+        return Ok(Term::Section(name.trim().to_string()));
+    }
+
+    let lower = text.to_lowercase();
+    let term = match lower.as_str() {
+        "all" | "view all" => Some(Term::All),
+        "overdue" | "od" => Some(Term::Overdue),
+        "no date" | "no due date" => Some(Term::NoDate),
+        "recurring" => Some(Term::Recurring),
+        "subtask" | "subtasks" => Some(Term::Subtask),
+        "no labels" => Some(Term::NoLabels),
+        "p1" => Some(Term::Priority(4)),
+        "p2" => Some(Term::Priority(3)),
+        "p3" => Some(Term::Priority(2)),
+        "p4" | "no priority" => Some(Term::Priority(1)),
+        _ => None,
+    };
+    if let Some(term) = term {
+        return Ok(term);
+    }
+
+    if let Some((key, value)) = lower.split_once(':') {
+        let value = value.trim();
+        let date = || parse_date(value)?.ok_or_else(|| format!("unknown date \"{}\"", value));
+        return match key.trim() {
+            "search" => Ok(Term::Search(value.to_string())),
+            "due" | "date" => Ok(Term::DueOn(date()?)),
+            "due before" | "date before" => Ok(Term::DueBefore(date()?)),
+            "due after" | "date after" => Ok(Term::DueAfter(date()?)),
+            _ => Err(format!("unknown filter \"{}\"", text)),
+        };
+    }
+
+    // "7 days" is the coming week, "-7 days" the past one
+    if let Some(days) = parse_days(lower.strip_prefix("next ").unwrap_or(&lower))? {
+        return Ok(Term::DueWithin(days));
+    }
+    if let Some(date) = parse_date(&lower)? {
+        return Ok(Term::DueOn(date));
+    }
+
+    Err(format!("unknown filter \"{}\"", text))
+}
+
+/// `today`, `tomorrow`, `yesterday`, `+3 days`, `-2 weeks`, `2024-05-01`
+/// or `may 1`. `None` when it isn't a date, an error when it is too far
+/// away to be one.
+fn parse_date(text: &str) -> Result<Option<DateSpec>, String> {
+    match text {
+        "today" => return Ok(Some(DateSpec::DaysFromToday(0))),
+        "tomorrow" => return Ok(Some(DateSpec::DaysFromToday(1))),
+        "yesterday" => return Ok(Some(DateSpec::DaysFromToday(-1))),
+        _ => {}
+    }
+    if let Some(days) = parse_days(text.strip_prefix('+').unwrap_or(text))? {
+        return Ok(Some(DateSpec::DaysFromToday(days)));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(Some(DateSpec::Date(date)));
+    }
+
+    let with_year = format!("{} {}", text, Local::now().year());
+    Ok(["%b %d %Y", "%d %b %Y", "%B %d %Y", "%d %B %Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&with_year, format).ok())
+        .map(DateSpec::Date))
+}
+
+/// `3 days` or `2 weeks`, as a number of days, at most `MAX_DAYS` either
+/// way.
+fn parse_days(text: &str) -> Result<Option<i64>, String> {
+    let mut words = text.split_whitespace();
+    let (Some(count), Some(unit), None) = (words.next(), words.next(), words.next()) else {
+        return Ok(None);
+    };
+    let Ok(count) = count.parse::<i64>() else {
+        return Ok(None);
+    };
+    let days = match unit {
+        "day" | "days" => Some(count),
+        "week" | "weeks" => count.checked_mul(7),
+        _ => return Ok(None),
+    };
+    days.filter(|days| days.abs() <= MAX_DAYS)
+        .map(Some)
+        .ok_or_else(|| format!("\"{}\" is too far away", text))
+}
+
+/// Case insensitive, with `*` standing for any run of characters.
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let name = name.to_lowercase();
+    let mut parts = pattern.split('*');
+
+    let Some(mut rest) = name.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, 15).unwrap()
+    }
+
+    fn task(json: serde_json::Value) -> Task {
+        Task::from_json("1".to_string(), &json)
+    }
+
+    fn due(days: i64) -> String {
+        (today() + TimeDelta::days(days)).to_string()
+    }
+
+    fn projects() -> Vec<Project> {
+        vec![
+            Project::from_json("work".to_string(), &json!({ "name": "Work" })),
+            Project::from_json(
+                "clients".to_string(),
+                &json!({ "name": "Clients", "parent_id": "work" }),
+            ),
+        ]
+    }
+
+    fn sections() -> Vec<Section> {
+        vec![Section::from_json(
+            "meetings".to_string(),
+            &json!({ "project_id": "clients", "name": "Meetings" }),
+        )]
+    }
+
+    fn matches(query: &str, task: &Task) -> bool {
+        let (projects, sections) = (projects(), sections());
+        let context = Context {
+            today: today(),
+            projects: &projects,
+            sections: &sections,
+        };
+        Query::parse(query)
+            .unwrap_or_else(|e| panic!("{}: {}", query, e))
+            .matches(task, &context)
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let p1 = task(json!({ "priority": 4 }));
+        let p2 = task(json!({ "priority": 3 }));
+        let p2_work = task(json!({ "priority": 3, "labels": ["work"] }));
+
+        assert!(matches("p1 | p2 & @work", &p1));
+        assert!(!matches("p1 | p2 & @work", &p2));
+        assert!(matches("p1 | p2 & @work", &p2_work));
+        assert!(!matches("(p1 | p2) & @work", &p1));
+        assert!(matches("(p1 | p2) & @work", &p2_work));
+        assert!(matches("p2 & @work | p1", &p1));
+    }
+
+    #[test]
+    fn comma_is_or_and_not_negates() {
+        let p1 = task(json!({ "priority": 4 }));
+        assert!(matches("p2, p1", &p1));
+        assert!(!matches("!p1", &p1));
+        assert!(matches("!!p1", &p1));
+        assert!(matches("!(p2 | p3)", &p1));
+        assert!(!matches("!p1 & all", &p1));
+    }
+
+    #[test]
+    fn escapes_make_operators_part_of_a_name() {
+        let task = task(json!({ "labels": ["r&d", "a|b", "x(y)", "wow!"] }));
+        assert!(matches("@r\\&d", &task));
+        assert!(matches("@a\\|b", &task));
+        assert!(matches("@x\\(y\\)", &task));
+        // a ! inside a name isn't a negation
+        assert!(matches("@wow!", &task));
+    }
+
+    #[test]
+    fn dates() {
+        let today = task(json!({ "due_date": due(0) }));
+        let yesterday = task(json!({ "due_date": due(-1) }));
+        let next_week = task(json!({ "due_date": due(7) }));
+        let undated = task(json!({}));
+
+        assert!(matches("today", &today));
+        assert!(!matches("today", &yesterday));
+        assert!(matches("overdue", &yesterday));
+        assert!(matches("od", &yesterday));
+        assert!(!matches("overdue", &today));
+        assert!(!matches("overdue", &undated));
+        assert!(matches("no date", &undated));
+        assert!(!matches("no date", &today));
+
+        assert!(matches("due before: today", &yesterday));
+        assert!(!matches("due before: today", &today));
+        assert!(!matches("due before: today", &undated));
+        assert!(matches("due after: tomorrow", &next_week));
+        assert!(matches("due: +7 days", &next_week));
+        assert!(matches(&format!("due: {}", due(-1)), &yesterday));
+        assert!(matches("yesterday", &yesterday));
+
+        assert!(matches("7 days", &today));
+        assert!(matches("next 7 days", &next_week));
+        assert!(!matches("6 days", &next_week));
+        assert!(matches("-7 days", &yesterday));
+        assert!(!matches("-7 days", &next_week));
+    }
+
+    #[test]
+    fn dates_out_of_range() {
+        for query in [
+            "due before: +99999999 days",
+            "9999999999999999 weeks",
+            "-400000 days",
+        ] {
+            assert!(Query::parse(query).is_err(), "{:?} parsed", query);
+        }
+        assert!(matches(
+            "next 52 weeks",
+            &task(json!({ "due_date": due(300) }))
+        ));
+    }
+
+    #[test]
+    fn projects_sections_and_labels() {
+        let task = task(json!({
+            "project_id": "clients",
+            "section_id": "meetings",
+            "labels": ["Errands"],
+        }));
+
+        assert!(matches("#Clients", &task));
+        assert!(matches("#clients", &task));
+        assert!(!matches("#Work", &task));
+        assert!(matches("##Work", &task));
+        assert!(matches("##Clients", &task));
+        assert!(!matches("##Home", &task));
+        assert!(matches("/Meetings", &task));
+        assert!(!matches("/Notes", &task));
+        assert!(matches("@errands", &task));
+        assert!(!matches("no labels", &task));
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(name_matches("shop*", "Shopping"));
+        assert!(name_matches("*ing", "Shopping"));
+        assert!(name_matches("s*p*g", "Shopping"));
+        assert!(name_matches("*", "anything"));
+        assert!(name_matches("shopping", "SHOPPING"));
+        assert!(!name_matches("shop", "Shopping"));
+        assert!(!name_matches("*shop", "Shopping"));
+        assert!(!name_matches("s*x*g", "Shopping"));
+
+        let task = task(json!({ "project_id": "clients", "labels": ["waiting-on"] }));
+        assert!(matches("#Cli*", &task));
+        assert!(matches("##W*k", &task));
+        assert!(matches("@waiting*", &task));
+    }
+
+    #[test]
+    fn search() {
+        let task = task(json!({ "content": "Buy Milk" }));
+        assert!(matches("search: milk", &task));
+        assert!(!matches("search: bread", &task));
+    }
+
+    #[test]
+    fn parse_errors() {
+        for query in [
+            "",
+            "p1 &",
+            "& p1",
+            "(today",
+            "today)",
+            "p1 p2",
+            "bogus",
+            "due: someday",
+            "priority: high",
+            "!",
+        ] {
+            assert!(Query::parse(query).is_err(), "{:?} parsed", query);
+        }
+    }
+}
@@ -0,0 +1,524 @@
+use chrono::{Datelike, Days, Local, Month, Months, NaiveDate, Weekday};
+use serde_json::json;
+
+use crate::{
+    projects::{Collaborator, Project},
+    sections::Section,
+};
+
+/// A task written on one line, e.g. `Buy milk tomorrow p1 #Home @errands`.
+#[derive(Debug, Default, PartialEq)]
+pub struct QuickAdd {
+    pub content: String,
+    /// Name of the project after `#`.
+    pub project: Option<String>,
+    /// Name of the section after `/`.
+    pub section: Option<String>,
+    pub labels: Vec<String>,
+    /// In the API's numbering, 4 is P1.
+    pub priority: Option<u8>,
+    /// Who to assign the task to, after `+`.
+    pub assignee: Option<String>,
+    /// What was written between `{` and `}`.
+    pub deadline: Option<String>,
+    /// Date phrase at the end, left for Todoist to understand.
+    pub due_string: Option<String>,
+    /// `#` and `/` words naming no project or section, kept in the content
+    /// like Todoist does, e.g. `#1` in `review #1`.
+    pub unmatched: Vec<String>,
+}
+
+/// The ids the names in a `QuickAdd` stand for.
+#[derive(Debug, Default)]
+pub struct Resolved {
+    pub project_id: Option<String>,
+    pub section_id: Option<String>,
+    pub assignee: Option<Collaborator>,
+    pub deadline: Option<NaiveDate>,
+    /// What couldn't be made sense of, the task waits until it's fixed.
+    pub problems: Vec<String>,
+    /// Worth knowing, but not in the way of adding the task.
+    pub hints: Vec<String>,
+}
+
+impl QuickAdd {
+    /// Picks the tokens out of `text`, matching `#` and `/` against the
+    /// names of `projects` and `sections` so that names with spaces work.
+    pub fn parse(text: &str, projects: &[Project], sections: &[Section]) -> QuickAdd {
+        let mut quick_add = QuickAdd::default();
+        let text = quick_add.take_deadline(text);
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut rest = Vec::new();
+
+        let mut i = 0;
+        while i < words.len() {
+            let word = words[i];
+            if let Some(name) = word.strip_prefix('#').filter(|name| !name.is_empty()) {
+                let names = projects.iter().map(|project| project.name.as_str());
+                if let Some((project, len)) = match_name(name, &words[i + 1..], names) {
+                    quick_add.project = Some(project);
+                    i += len;
+                    continue;
+                }
+                quick_add.unmatched.push(word.to_string());
+                rest.push(word);
+            } else if let Some(name) = word.strip_prefix('/').filter(|name| !name.is_empty()) {
+                let names = sections.iter().map(|section| section.name.as_str());
+                if let Some((section, len)) = match_name(name, &words[i + 1..], names) {
+                    quick_add.section = Some(section);
+                    i += len;
+                    continue;
+                }
+                quick_add.unmatched.push(word.to_string());
+                rest.push(word);
+            } else if let Some(label) = word.strip_prefix('@').filter(|label| !label.is_empty()) {
+                if !quick_add.labels.iter().any(|existing| existing == label) {
+                    quick_add.labels.push(label.to_string());
+                }
+            } else if let Some(name) = word.strip_prefix('+').filter(|name| !name.is_empty()) {
+                quick_add.assignee = Some(name.to_string());
+            } else if let Some(priority) = parse_priority(word) {
+                quick_add.priority = Some(priority);
+            } else {
+                rest.push(word);
+            }
+            i += 1;
+        }
+
+        let start = date_phrase_start(&rest);
+        if start < rest.len() {
+            quick_add.due_string = Some(rest[start..].join(" "));
+        }
+        quick_add.content = rest[..start].join(" ");
+        quick_add
+    }
+
+    /// Keeps what is between braces as the deadline and returns the rest.
+    /// An unclosed brace takes the rest of the line, as it's being typed.
+    fn take_deadline(&mut self, text: &str) -> String {
+        let mut rest = String::new();
+        let mut remaining = text;
+        while let Some(open) = remaining.find('{') {
+            rest.push_str(&remaining[..open]);
+            rest.push(' ');
+            let inside = &remaining[open + 1..];
+            let (deadline, after) = inside.split_once('}').unwrap_or((inside, ""));
+            self.deadline = Some(deadline.trim().to_string()).filter(|d| !d.is_empty());
+            remaining = after;
+        }
+        rest.push_str(remaining);
+        rest
+    }
+
+    /// Looks the names up, the project falling back to `default_project_id`.
+    /// `collaborators` are the people in that project, `None` while they
+    /// are being fetched.
+    pub fn resolve(
+        &self,
+        projects: &[Project],
+        sections: &[Section],
+        collaborators: Option<&[Collaborator]>,
+        default_project_id: Option<&str>,
+    ) -> Resolved {
+        let mut resolved = Resolved::default();
+
+        let project = match &self.project {
+            Some(name) => {
+                let project = find_project(projects, name);
+                if project.is_none() {
+                    resolved
+                        .problems
+                        .push(format!("No project named \"{}\"", name));
+                }
+                project
+            }
+            None => default_project_id.and_then(|id| projects.iter().find(|p| p.id == id)),
+        };
+        resolved.project_id = project.map(|project| project.id.clone());
+        resolved.hints.extend(self.unmatched.iter().map(|word| {
+            let kind = if word.starts_with('#') {
+                "project"
+            } else {
+                "section"
+            };
+            format!(
+                "No {} called \"{}\", it stays in the text",
+                kind,
+                &word[1..]
+            )
+        }));
+
+        if let (Some(name), Some(project)) = (&self.section, project) {
+            match sections.iter().find(|section| {
+                section.project_id == project.id && section.name.eq_ignore_ascii_case(name)
+            }) {
+                Some(section) => resolved.section_id = Some(section.id.clone()),
+                None => resolved
+                    .problems
+                    .push(format!("No section named \"{}\" in {}", name, project.name)),
+            }
+        }
+
+        if let (Some(name), Some(project)) = (&self.assignee, project) {
+            match collaborators {
+                _ if !project.is_shared => resolved.problems.push(format!(
+                    "{} isn't shared, there's no one to assign to",
+                    project.name
+                )),
+                None => resolved
+                    .problems
+                    .push("Looking up who's in the project...".to_string()),
+                Some(collaborators) => match collaborators
+                    .iter()
+                    .find(|collaborator| collaborator.answers_to(name))
+                {
+                    Some(collaborator) => resolved.assignee = Some(collaborator.clone()),
+                    None => resolved
+                        .problems
+                        .push(format!("No one called \"{}\" in {}", name, project.name)),
+                },
+            }
+        }
+
+        if let Some(deadline) = &self.deadline {
+            resolved.deadline = parse_date(deadline, Local::now().date_naive());
+            if resolved.deadline.is_none() {
+                resolved
+                    .problems
+                    .push(format!("Can't tell what day \"{}\" is", deadline));
+            }
+        }
+        resolved
+    }
+
+    /// The JSON to create the task with, failing on anything that didn't
+    /// resolve.
+    pub fn to_json(&self, resolved: &Resolved) -> Result<serde_json::Value, String> {
+        if !resolved.problems.is_empty() {
+            return Err(resolved.problems.join(", "));
+        }
+        let mut json = json!({ "content": self.content });
+        if let Some(project_id) = &resolved.project_id {
+            json["project_id"] = json!(project_id);
+        }
+        if let Some(section_id) = &resolved.section_id {
+            json["section_id"] = json!(section_id);
+        }
+        if !self.labels.is_empty() {
+            json["labels"] = json!(self.labels);
+        }
+        if let Some(priority) = self.priority {
+            json["priority"] = json!(priority);
+        }
+        if let Some(assignee) = &resolved.assignee {
+            json["assignee_id"] = json!(assignee.id);
+        }
+        if let Some(deadline) = resolved.deadline {
+            json["deadline_date"] = json!(deadline.to_string());
+        }
+        if let Some(due_string) = &self.due_string {
+            json["due_string"] = json!(due_string);
+        }
+        Ok(json)
+    }
+}
+
+pub fn find_project<'a>(projects: &'a [Project], name: &str) -> Option<&'a Project> {
+    projects
+        .iter()
+        .find(|project| project.name.eq_ignore_ascii_case(name))
+}
+
+/// The longest of `names` starting with `first` and continuing with the
+/// following words, and how many words it took.
+fn match_name<'a>(
+    first: &str,
+    following: &[&str],
+    names: impl Iterator<Item = &'a str> + Clone,
+) -> Option<(String, usize)> {
+    let mut typed = first.to_string();
+    let mut best = None;
+    for len in 1..=following.len() + 1 {
+        if len > 1 {
+            typed = format!("{} {}", typed, following[len - 2]);
+        }
+        if let Some(name) = names.clone().find(|name| name.eq_ignore_ascii_case(&typed)) {
+            best = Some((name.to_string(), len));
+        }
+    }
+    best
+}
+
+/// `p1` to `p4`, as the API counts them.
+pub fn parse_priority(word: &str) -> Option<u8> {
+    match word.to_lowercase().as_str() {
+        "p1" => Some(4),
+        "p2" => Some(3),
+        "p3" => Some(2),
+        "p4" => Some(1),
+        _ => None,
+    }
+}
+
+fn is_number(word: &str) -> bool {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// `5pm`, `17:30`, `9:15am`.
+fn is_time(word: &str) -> bool {
+    let word = word.trim_end_matches("am").trim_end_matches("pm");
+    !word.is_empty()
+        && word.chars().all(|c| c.is_ascii_digit() || c == ':')
+        && word.chars().next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// `2024-05-01`, `1/5`, `05.01`.
+fn is_date(word: &str) -> bool {
+    word.contains(['-', '/', '.'])
+        && word
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | '/' | '.'))
+}
+
+fn is_weekday_or_month(word: &str) -> bool {
+    word.parse::<Weekday>().is_ok() || word.parse::<Month>().is_ok()
+}
+
+/// Words that can follow the start of a date phrase.
+fn is_date_word(word: &str) -> bool {
+    matches!(
+        word,
+        "day"
+            | "days"
+            | "week"
+            | "weeks"
+            | "weekend"
+            | "month"
+            | "months"
+            | "year"
+            | "years"
+            | "hour"
+            | "hours"
+            | "morning"
+            | "afternoon"
+            | "evening"
+            | "night"
+            | "noon"
+            | "midnight"
+            | "other"
+            | "and"
+            | "end"
+            | "of"
+            | "at"
+    )
+}
+
+/// Whether a date phrase can start at `word`, given the word after it.
+fn starts_date(word: &str, next: Option<&str>) -> bool {
+    let next = next.unwrap_or_default();
+    match word {
+        "today" | "tod" | "tomorrow" | "tom" | "tmr" | "tonight" | "every" | "ev" => true,
+        "in" => is_number(next),
+        "on" => is_weekday_or_month(next) || is_number(next) || is_date(next),
+        "at" => is_time(next) || matches!(next, "noon" | "midnight"),
+        "next" | "this" => is_weekday_or_month(next) || is_date_word(next),
+        word => is_weekday_or_month(word) || is_date(word),
+    }
+}
+
+fn continues_date(word: &str) -> bool {
+    starts_date(word, None)
+        || is_date_word(word)
+        || matches!(word, "in" | "on" | "next" | "this")
+        || is_number(word)
+        || is_time(word)
+}
+
+/// Index of the first word of the date phrase ending `words`, or its length
+/// when there is none. The first word always stays, it's the task.
+fn date_phrase_start(words: &[&str]) -> usize {
+    let lower: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+    (1..lower.len())
+        .find(|&start| {
+            starts_date(&lower[start], lower.get(start + 1).map(String::as_str))
+                && lower[start..].iter().all(|word| continues_date(word))
+        })
+        .unwrap_or(words.len())
+}
+
+/// Understands a few ways of naming a day: `2024-05-01`, `today`,
+/// `tomorrow`, `friday`, `next week`, `in 3 days` and `12 May`.
+pub fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.trim().to_lowercase();
+    if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        return Some(date);
+    }
+    let words: Vec<&str> = text.split_whitespace().collect();
+    match words.as_slice() {
+        ["today" | "tod"] => Some(today),
+        ["tomorrow" | "tom" | "tmr"] => today.checked_add_days(Days::new(1)),
+        ["next", "week"] => today.checked_add_days(Days::new(7)),
+        ["next", "month"] => today.checked_add_months(Months::new(1)),
+        ["next", weekday] | [weekday] if weekday.parse::<Weekday>().is_ok() => {
+            let weekday = weekday.parse::<Weekday>().ok()?;
+            let days =
+                (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            today.checked_add_days(Days::new(if days == 0 { 7 } else { days as u64 }))
+        }
+        ["in", count, unit] => {
+            let count: u32 = count.parse().ok()?;
+            match unit.trim_end_matches('s') {
+                "day" => today.checked_add_days(Days::new(count as u64)),
+                "week" => today.checked_add_days(Days::new(count as u64 * 7)),
+                "month" => today.checked_add_months(Months::new(count)),
+                _ => None,
+            }
+        }
+        [day, month] | [month, day] if month.parse::<Month>().is_ok() => {
+            let month = month.parse::<Month>().ok()?.number_from_month();
+            let day: u32 = day.trim_end_matches(char::is_alphabetic).parse().ok()?;
+            let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+            if date < today {
+                NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+            } else {
+                Some(date)
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn projects() -> Vec<Project> {
+        let mut inbox = Project::from_json("inbox".to_string(), &json!({ "name": "Inbox" }));
+        inbox.is_inbox_project = true;
+        vec![
+            inbox,
+            Project::from_json("home".to_string(), &json!({ "name": "Home" })),
+            Project::from_json("side".to_string(), &json!({ "name": "Side Project" })),
+        ]
+    }
+
+    fn sections() -> Vec<Section> {
+        vec![Section::from_json(
+            "shop".to_string(),
+            &json!({ "project_id": "home", "name": "Shop" }),
+        )]
+    }
+
+    fn parse(text: &str) -> QuickAdd {
+        QuickAdd::parse(text, &projects(), &sections())
+    }
+
+    fn resolve(quick_add: &QuickAdd) -> Resolved {
+        quick_add.resolve(&projects(), &sections(), Some(&[]), Some("inbox"))
+    }
+
+    #[test]
+    fn picks_out_every_token() {
+        let quick_add = parse("Buy milk tomorrow p1 #Home /Shop @errands @errands {may 1}");
+        assert_eq!(
+            quick_add,
+            QuickAdd {
+                content: "Buy milk".to_string(),
+                project: Some("Home".to_string()),
+                section: Some("Shop".to_string()),
+                labels: vec!["errands".to_string()],
+                priority: Some(4),
+                deadline: Some("may 1".to_string()),
+                due_string: Some("tomorrow".to_string()),
+                ..QuickAdd::default()
+            }
+        );
+
+        let resolved = resolve(&quick_add);
+        assert!(resolved.problems.is_empty(), "{:?}", resolved.problems);
+        let json = quick_add.to_json(&resolved).unwrap();
+        assert_eq!(json["content"], "Buy milk");
+        assert_eq!(json["project_id"], "home");
+        assert_eq!(json["section_id"], "shop");
+        assert_eq!(json["priority"], 4);
+        assert_eq!(json["due_string"], "tomorrow");
+        assert!(json["deadline_date"].as_str().unwrap().ends_with("-05-01"));
+    }
+
+    #[test]
+    fn project_names_can_have_spaces() {
+        let quick_add = parse("Plan #side project launch");
+        assert_eq!(quick_add.project.as_deref(), Some("Side Project"));
+        assert_eq!(quick_add.content, "Plan launch");
+    }
+
+    #[test]
+    fn unknown_projects_and_sections_stay_in_the_text() {
+        let quick_add = parse("review #1 PR p2");
+        assert_eq!(quick_add.content, "review #1 PR");
+        assert_eq!(quick_add.project, None);
+        assert_eq!(quick_add.priority, Some(3));
+
+        let resolved = resolve(&quick_add);
+        assert!(resolved.problems.is_empty());
+        assert_eq!(resolved.hints.len(), 1);
+        assert_eq!(resolved.project_id.as_deref(), Some("inbox"));
+        assert!(quick_add.to_json(&resolved).is_ok());
+
+        let quick_add = parse("check /etc/hosts");
+        assert_eq!(quick_add.content, "check /etc/hosts");
+        assert_eq!(quick_add.section, None);
+        assert!(resolve(&quick_add).problems.is_empty());
+    }
+
+    #[test]
+    fn a_section_of_another_project_is_a_problem() {
+        let quick_add = parse("Buy milk #Side Project /Shop");
+        let resolved = resolve(&quick_add);
+        assert_eq!(resolved.problems.len(), 1);
+        assert!(quick_add.to_json(&resolved).is_err());
+    }
+
+    #[test]
+    fn the_first_word_is_never_a_date() {
+        let quick_add = parse("Friday drinks");
+        assert_eq!(quick_add.content, "Friday drinks");
+        assert_eq!(quick_add.due_string, None);
+
+        let quick_add = parse("Call mum every other week");
+        assert_eq!(quick_add.content, "Call mum");
+        assert_eq!(quick_add.due_string.as_deref(), Some("every other week"));
+    }
+
+    #[test]
+    fn only_tokens_leave_no_content() {
+        assert_eq!(parse("p1").content, "");
+        assert_eq!(parse("#Home @errands").content, "");
+    }
+
+    #[test]
+    fn an_unclosed_deadline_takes_the_rest() {
+        let quick_add = parse("Taxes {next week");
+        assert_eq!(quick_add.content, "Taxes");
+        assert_eq!(quick_add.deadline.as_deref(), Some("next week"));
+    }
+
+    #[test]
+    fn parses_dates() {
+        // a Wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        assert_eq!(parse_date("today", today), date(2024, 5, 15));
+        assert_eq!(parse_date("tom", today), date(2024, 5, 16));
+        assert_eq!(parse_date("friday", today), date(2024, 5, 17));
+        assert_eq!(parse_date("wednesday", today), date(2024, 5, 22));
+        assert_eq!(parse_date("in 2 weeks", today), date(2024, 5, 29));
+        assert_eq!(parse_date("12 may", today), date(2025, 5, 12));
+        assert_eq!(parse_date("June 1st", today), date(2024, 6, 1));
+        assert_eq!(parse_date("2024-01-02", today), date(2024, 1, 2));
+        assert_eq!(parse_date("someday", today), None);
+    }
+}
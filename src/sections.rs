@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Section {
     pub id: String,
     pub project_id: String,
@@ -8,6 +8,19 @@ pub struct Section {
     pub name: String,
 }
 
+impl Section {
+    /// Builds a section locally from the JSON used to create it, for when
+    /// Todoist hasn't answered yet.
+    pub fn from_json(id: String, json: &serde_json::Value) -> Section {
+        Section {
+            id,
+            project_id: json["project_id"].as_str().unwrap_or_default().to_string(),
+            order: json["order"].as_i64().unwrap_or(0) as i32,
+            name: json["name"].as_str().unwrap_or_default().to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Sections {
     pub sections: Vec<Section>,
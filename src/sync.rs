@@ -0,0 +1,530 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::{
+    filters::SavedFilter,
+    labels::Label,
+    projects::{ListType, Project},
+    sections::Section,
+    tasks::{Due, Task},
+};
+
+/// Sync token that asks Todoist for everything instead of a delta.
+pub const FULL_SYNC_TOKEN: &str = "*";
+
+/// Only what `api_calls::fetch_filters` asks for.
+pub const FILTER_RESOURCE_TYPES: &str = r#"["filters"]"#;
+
+/// The resources we keep locally, in the form the Sync API expects.
+pub const RESOURCE_TYPES: &str = r#"["projects","items","sections","labels","filters"]"#;
+
+/// What changed since the sync token we sent.
+#[derive(Debug, Clone, Default)]
+pub struct SyncDelta {
+    pub sync_token: String,
+    /// When set the changes are the complete data set, not a delta.
+    pub full_sync: bool,
+    pub projects: Changes<Project>,
+    pub tasks: Changes<Task>,
+    pub sections: Changes<Section>,
+    pub labels: Changes<Label>,
+    pub filters: Changes<SavedFilter>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Changes<T> {
+    /// Added or updated.
+    pub updated: Vec<T>,
+    /// Ids of deleted, archived or completed items.
+    pub removed: Vec<String>,
+}
+
+impl<T> Default for Changes<T> {
+    fn default() -> Self {
+        Changes {
+            updated: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+impl<T> Changes<T> {
+    /// Applies the changes to `existing`, matching items with `id`.
+    pub fn merge_into(self, existing: &mut Vec<T>, id: impl Fn(&T) -> &str) {
+        existing.retain(|item| !self.removed.iter().any(|removed| removed == id(item)));
+        for item in self.updated {
+            match existing.iter_mut().find(|current| id(current) == id(&item)) {
+                Some(current) => *current = item,
+                None => existing.push(item),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncResponse {
+    pub sync_token: String,
+    #[serde(default)]
+    pub full_sync: bool,
+    #[serde(default)]
+    pub projects: Vec<SyncProject>,
+    #[serde(default)]
+    pub items: Vec<SyncItem>,
+    #[serde(default)]
+    pub sections: Vec<SyncSection>,
+    #[serde(default)]
+    pub labels: Vec<SyncLabel>,
+    #[serde(default)]
+    pub filters: Vec<SyncFilter>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncProject {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub color: String,
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub child_order: i32,
+    #[serde(default)]
+    pub shared: bool,
+    #[serde(default)]
+    pub is_favorite: bool,
+    #[serde(default)]
+    pub inbox_project: bool,
+    #[serde(default)]
+    pub team_inbox: bool,
+    pub view_style: Option<ListType>,
+    #[serde(default)]
+    pub is_deleted: bool,
+    #[serde(default)]
+    pub is_archived: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncItem {
+    pub id: String,
+    pub project_id: String,
+    pub section_id: Option<String>,
+    pub content: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub child_order: i32,
+    #[serde(default = "default_priority")]
+    pub priority: u8,
+    pub due: Option<SyncDue>,
+    #[serde(default)]
+    pub added_at: Option<String>,
+    #[serde(default)]
+    pub added_by_uid: Option<String>,
+    pub responsible_uid: Option<String>,
+    pub assigned_by_uid: Option<String>,
+    #[serde(default)]
+    pub checked: bool,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncDue {
+    /// `YYYY-MM-DD`, or a full date time for tasks with a time.
+    pub date: String,
+    #[serde(default)]
+    pub string: String,
+    #[serde(default)]
+    pub is_recurring: bool,
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncSection {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub section_order: i32,
+    #[serde(default)]
+    pub is_deleted: bool,
+    #[serde(default)]
+    pub is_archived: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncLabel {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub color: String,
+    #[serde(default)]
+    pub item_order: i32,
+    #[serde(default)]
+    pub is_favorite: bool,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncFilter {
+    pub id: String,
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub item_order: i32,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+fn default_priority() -> u8 {
+    1
+}
+
+impl From<SyncResponse> for SyncDelta {
+    fn from(response: SyncResponse) -> Self {
+        let mut delta = SyncDelta {
+            sync_token: response.sync_token,
+            full_sync: response.full_sync,
+            ..Default::default()
+        };
+
+        for project in response.projects {
+            if project.is_deleted || project.is_archived {
+                delta.projects.removed.push(project.id);
+            } else {
+                delta.projects.updated.push(project.into());
+            }
+        }
+
+        for item in response.items {
+            if item.is_deleted || item.checked {
+                delta.tasks.removed.push(item.id);
+            } else {
+                delta.tasks.updated.push(item.into());
+            }
+        }
+
+        for section in response.sections {
+            if section.is_deleted || section.is_archived {
+                delta.sections.removed.push(section.id);
+            } else {
+                delta.sections.updated.push(section.into());
+            }
+        }
+
+        for label in response.labels {
+            if label.is_deleted {
+                delta.labels.removed.push(label.id);
+            } else {
+                delta.labels.updated.push(label.into());
+            }
+        }
+
+        for filter in response.filters {
+            if filter.is_deleted {
+                delta.filters.removed.push(filter.id);
+            } else {
+                delta.filters.updated.push(filter.into());
+            }
+        }
+
+        delta
+    }
+}
+
+impl From<SyncProject> for Project {
+    fn from(project: SyncProject) -> Self {
+        Project {
+            id: project.id,
+            name: project.name,
+            comment_count: 0,
+            order: project.child_order,
+            color: project.color,
+            is_shared: project.shared,
+            is_favorite: project.is_favorite,
+            is_inbox_project: project.inbox_project,
+            is_team_inbox: project.team_inbox,
+            view_style: project.view_style.unwrap_or(ListType::List),
+            url: String::new(),
+            parent_id: project.parent_id,
+        }
+    }
+}
+
+impl From<SyncSection> for Section {
+    fn from(section: SyncSection) -> Self {
+        Section {
+            id: section.id,
+            project_id: section.project_id,
+            order: section.section_order,
+            name: section.name,
+        }
+    }
+}
+
+impl From<SyncLabel> for Label {
+    fn from(label: SyncLabel) -> Self {
+        Label {
+            id: label.id,
+            name: label.name,
+            color: label.color,
+            order: label.item_order,
+            is_favorite: label.is_favorite,
+        }
+    }
+}
+
+impl From<SyncFilter> for SavedFilter {
+    fn from(filter: SyncFilter) -> Self {
+        SavedFilter {
+            id: Some(filter.id),
+            name: filter.name,
+            query: filter.query,
+            key: None,
+            order: filter.item_order,
+        }
+    }
+}
+
+impl From<SyncItem> for Task {
+    fn from(item: SyncItem) -> Self {
+        Task {
+            url: String::new(),
+            id: item.id,
+            project_id: item.project_id,
+            section_id: item.section_id,
+            content: item.content,
+            description: item.description,
+            is_completed: item.checked,
+            labels: item.labels,
+            parent_id: item.parent_id,
+            order: item.child_order,
+            priority: item.priority,
+            due: item.due.and_then(SyncDue::into_due),
+            comment_count: 0,
+            created_at: item.added_at.unwrap_or_default(),
+            creator_id: item.added_by_uid.unwrap_or_default(),
+            assignee_id: item.responsible_uid,
+            assigner_id: item.assigned_by_uid,
+            duration: None,
+        }
+    }
+}
+
+impl SyncDue {
+    /// Sync returns a single `date` that holds the time too, when there is one.
+    fn into_due(self) -> Option<Due> {
+        let date = NaiveDate::parse_from_str(self.date.get(..10)?, "%Y-%m-%d").ok()?;
+        let datetime = (self.date.len() > 10).then(|| self.date.trim_end_matches('Z').to_string());
+
+        Some(Due {
+            string: self.string,
+            date,
+            is_recurring: self.is_recurring,
+            datetime,
+            timezone: self.timezone,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::App;
+
+    /// A full sync as Todoist sends it, trimmed to the fields we read.
+    const FULL_SYNC: &str = r#"{
+        "sync_token": "token-1",
+        "full_sync": true,
+        "projects": [
+            {"id": "p1", "name": "Inbox", "color": "grey", "parent_id": null, "child_order": 0,
+             "inbox_project": true, "view_style": "list", "is_deleted": false, "is_archived": false},
+            {"id": "p2", "name": "Work", "color": "red", "parent_id": null, "child_order": 1,
+             "shared": true, "view_style": "board", "is_deleted": false, "is_archived": false},
+            {"id": "p3", "name": "Old", "color": "red", "parent_id": null, "child_order": 2,
+             "view_style": "list", "is_deleted": false, "is_archived": true}
+        ],
+        "items": [
+            {"id": "t1", "project_id": "p1", "section_id": null, "content": "Buy milk",
+             "description": "", "labels": ["errands"], "parent_id": null, "child_order": 1,
+             "priority": 4, "due": {"date": "2024-05-01", "string": "May 1", "is_recurring": false,
+             "timezone": null}, "added_at": "2024-04-01T10:00:00Z", "responsible_uid": null,
+             "assigned_by_uid": null, "checked": false, "is_deleted": false},
+            {"id": "t2", "project_id": "p2", "section_id": "s1", "content": "Write report",
+             "parent_id": null, "child_order": 1, "due": {"date": "2024-05-02T09:30:00Z",
+             "string": "every day at 9:30", "is_recurring": true, "timezone": "Europe/Paris"},
+             "responsible_uid": "u1", "assigned_by_uid": "u2", "checked": false, "is_deleted": false},
+            {"id": "t3", "project_id": "p2", "section_id": "s1", "content": "Outline",
+             "parent_id": "t2", "child_order": 1, "due": null, "responsible_uid": null,
+             "assigned_by_uid": null, "checked": true, "is_deleted": false}
+        ],
+        "sections": [
+            {"id": "s1", "project_id": "p2", "name": "Doing", "section_order": 1,
+             "is_deleted": false, "is_archived": false},
+            {"id": "s2", "project_id": "p2", "name": "Done", "section_order": 2,
+             "is_deleted": false, "is_archived": false}
+        ],
+        "labels": [
+            {"id": "l1", "name": "errands", "color": "blue", "item_order": 1, "is_favorite": true,
+             "is_deleted": false}
+        ],
+        "filters": [
+            {"id": "f1", "name": "Urgent", "query": "p1", "item_order": 1, "is_deleted": false}
+        ]
+    }"#;
+
+    /// What changed after `FULL_SYNC`: t1 edited, t2 deleted, t4 added,
+    /// Work renamed, Inbox left alone, Ideas added, s2 deleted and s3 added.
+    const INCREMENTAL_SYNC: &str = r#"{
+        "sync_token": "token-2",
+        "full_sync": false,
+        "projects": [
+            {"id": "p2", "name": "Work stuff", "color": "red", "parent_id": null,
+             "child_order": 1, "view_style": "board", "is_deleted": false, "is_archived": false},
+            {"id": "p4", "name": "Ideas", "color": "green", "parent_id": "p2", "child_order": 2,
+             "view_style": "list", "is_deleted": false, "is_archived": false}
+        ],
+        "items": [
+            {"id": "t1", "project_id": "p1", "section_id": null, "content": "Buy oat milk",
+             "parent_id": null, "child_order": 1, "priority": 3, "due": null,
+             "responsible_uid": null, "assigned_by_uid": null, "checked": false, "is_deleted": false},
+            {"id": "t2", "project_id": "p2", "section_id": "s1", "content": "Write report",
+             "parent_id": null, "child_order": 1, "due": null, "responsible_uid": null,
+             "assigned_by_uid": null, "checked": false, "is_deleted": true},
+            {"id": "t4", "project_id": "p4", "section_id": null, "content": "New idea",
+             "parent_id": null, "child_order": 1, "due": null, "responsible_uid": null,
+             "assigned_by_uid": null, "checked": false, "is_deleted": false}
+        ],
+        "sections": [
+            {"id": "s2", "project_id": "p2", "name": "Done", "section_order": 2,
+             "is_deleted": true, "is_archived": false},
+            {"id": "s3", "project_id": "p2", "name": "Later", "section_order": 3,
+             "is_deleted": false, "is_archived": false}
+        ],
+        "labels": [],
+        "filters": [
+            {"id": "f1", "name": "Urgent", "query": "p1", "item_order": 1, "is_deleted": true}
+        ]
+    }"#;
+
+    fn decode(json: &str) -> SyncDelta {
+        serde_json::from_str::<SyncResponse>(json)
+            .expect("recorded payload decodes")
+            .into()
+    }
+
+    fn ids<T>(items: &[T], id: impl Fn(&T) -> &str) -> Vec<String> {
+        let mut ids: Vec<String> = items.iter().map(|item| id(item).to_string()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn decodes_a_full_sync() {
+        let delta = decode(FULL_SYNC);
+        assert_eq!(delta.sync_token, "token-1");
+        assert!(delta.full_sync);
+
+        assert_eq!(ids(&delta.projects.updated, |p| &p.id), ["p1", "p2"]);
+        assert_eq!(delta.projects.removed, ["p3"]);
+        let work = &delta.projects.updated[1];
+        assert!(work.is_shared);
+        assert_eq!(work.view_style, ListType::Board);
+        assert!(delta.projects.updated[0].is_inbox_project);
+
+        // completed items are removed, like deleted ones
+        assert_eq!(ids(&delta.tasks.updated, |t| &t.id), ["t1", "t2"]);
+        assert_eq!(delta.tasks.removed, ["t3"]);
+        let milk = &delta.tasks.updated[0];
+        assert_eq!(milk.priority, 4);
+        assert_eq!(milk.labels, ["errands"]);
+        assert_eq!(milk.created_at, "2024-04-01T10:00:00Z");
+        let due = milk.due.as_ref().expect("t1 has a due date");
+        assert_eq!(due.date, NaiveDate::from_ymd_opt(2024, 5, 1).unwrap());
+        assert_eq!(due.datetime, None);
+
+        let report = &delta.tasks.updated[1];
+        assert_eq!(report.priority, 1);
+        assert_eq!(report.assignee_id.as_deref(), Some("u1"));
+        let due = report.due.as_ref().expect("t2 has a due date");
+        assert!(due.is_recurring);
+        assert_eq!(due.datetime.as_deref(), Some("2024-05-02T09:30:00"));
+        assert_eq!(due.timezone.as_deref(), Some("Europe/Paris"));
+
+        assert_eq!(ids(&delta.sections.updated, |s| &s.id), ["s1", "s2"]);
+        assert_eq!(delta.labels.updated[0].name, "errands");
+        assert_eq!(delta.filters.updated[0].id.as_deref(), Some("f1"));
+    }
+
+    #[test]
+    fn decodes_a_payload_without_resources() {
+        let delta = decode(r#"{"sync_token": "token-3"}"#);
+        assert!(!delta.full_sync);
+        assert!(delta.tasks.updated.is_empty() && delta.tasks.removed.is_empty());
+    }
+
+    #[test]
+    fn decoding_rejects_a_payload_without_a_token() {
+        assert!(serde_json::from_str::<SyncResponse>(r#"{"items": []}"#).is_err());
+    }
+
+    #[test]
+    fn merges_updates_additions_and_deletions() {
+        let full = decode(FULL_SYNC);
+        let mut projects = full.projects.updated;
+        let mut tasks = full.tasks.updated;
+        let mut sections = full.sections.updated;
+
+        let delta = decode(INCREMENTAL_SYNC);
+        assert_eq!(delta.tasks.removed, ["t2"]);
+        assert_eq!(delta.sections.removed, ["s2"]);
+        delta.projects.merge_into(&mut projects, |p| &p.id);
+        delta.tasks.merge_into(&mut tasks, |t| &t.id);
+        delta.sections.merge_into(&mut sections, |s| &s.id);
+
+        assert_eq!(ids(&projects, |p| &p.id), ["p1", "p2", "p4"]);
+        assert_eq!(projects[1].name, "Work stuff");
+        assert_eq!(projects[2].parent_id.as_deref(), Some("p2"));
+
+        assert_eq!(ids(&tasks, |t| &t.id), ["t1", "t4"]);
+        let milk = &tasks[0];
+        assert_eq!(milk.content, "Buy oat milk");
+        assert_eq!(milk.priority, 3);
+        assert!(milk.due.is_none());
+
+        assert_eq!(ids(&sections, |s| &s.id), ["s1", "s3"]);
+    }
+
+    #[test]
+    fn merging_additions_keeps_what_was_there() {
+        let mut tasks = decode(FULL_SYNC).tasks.updated;
+        let mut delta = decode(INCREMENTAL_SYNC).tasks;
+        delta.removed.clear();
+        delta.updated.retain(|task| task.id == "t4");
+        delta.merge_into(&mut tasks, |t| &t.id);
+        assert_eq!(ids(&tasks, |t| &t.id), ["t1", "t2", "t4"]);
+    }
+
+    #[test]
+    fn app_applies_an_incremental_sync_on_top_of_a_full_one() {
+        let mut app = App::new();
+        app.sync_filters = true;
+        app.apply_sync(decode(FULL_SYNC));
+        assert_eq!(app.sync_token.as_deref(), Some("token-1"));
+        assert_eq!(app.tasks.tasks.len(), 2);
+        assert_eq!(app.filters.account().len(), 1);
+
+        app.apply_sync(decode(INCREMENTAL_SYNC));
+        assert_eq!(app.sync_token.as_deref(), Some("token-2"));
+        assert_eq!(ids(&app.projects.projects, |p| &p.id), ["p1", "p2", "p4"]);
+        assert_eq!(ids(&app.tasks.tasks, |t| &t.id), ["t1", "t4"]);
+        assert_eq!(ids(&app.sections.sections, |s| &s.id), ["s1", "s3"]);
+        // labels weren't in the delta, so they stay
+        assert_eq!(app.labels.labels.len(), 1);
+        assert!(app.filters.account().is_empty());
+        assert!(!app.is_stale);
+    }
+}
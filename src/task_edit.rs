@@ -2,46 +2,62 @@ use ratatui::{
     style::{Modifier, Style},
     widgets::ListState,
 };
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{labels, tasks::Task};
 
 #[derive(Debug, Default, Clone)]
 pub struct TaskEdit<'a> {
     pub content: TextArea<'a>,
     pub description: TextArea<'a>,
     pub due_string: TextArea<'a>,
+    pub labels: TextArea<'a>,
+    /// In the API's numbering, 4 is P1.
+    pub priority: u8,
     pub currently_editing: CurrentlyEditing,
-    pub children: Vec<usize>,
+    /// Ids rather than indices, the task list is replaced by every refresh.
+    pub task_id: String,
+    pub children: Vec<String>,
     pub children_list_state: ListState,
-    pub current_task_index: usize,
 }
 
 impl<'a> TaskEdit<'a> {
-    pub fn new(
-        content: String,
-        description: String,
-        due_string: String,
-        children: Vec<usize>,
-        current_task_index: usize,
-        currently_editing: CurrentlyEditing,
-    ) -> Self {
+    pub fn new(task: &Task, children: Vec<String>, currently_editing: CurrentlyEditing) -> Self {
         let mut task_edit = TaskEdit {
-            content: TextArea::from(vec![content]),
-            description: TextArea::from(vec![description]),
-            due_string: TextArea::from(vec![due_string]),
+            content: TextArea::from(vec![task.content.clone()]),
+            description: TextArea::from(vec![task.description.clone()]),
+            due_string: TextArea::from(vec![task
+                .due
+                .as_ref()
+                .map_or(String::new(), |due| due.string.clone())]),
+            labels: TextArea::from(vec![labels::format_labels(&task.labels)]),
+            priority: task.priority,
             currently_editing,
+            task_id: task.id.clone(),
             children,
             children_list_state: ListState::default(),
-            current_task_index,
         };
 
+        // typing adds to the labels already there
+        task_edit.labels.move_cursor(CursorMove::End);
+
         // Automatically update cursor styles
         task_edit.update_cursor_styles();
 
         task_edit
     }
 
+    /// Follows a task created offline to the id Todoist gave it.
+    pub fn replace_id(&mut self, temp_id: &str, id: &str) {
+        for task_id in std::iter::once(&mut self.task_id).chain(self.children.iter_mut()) {
+            if task_id == temp_id {
+                *task_id = id.to_string();
+            }
+        }
+    }
+
     pub fn next(&mut self) {
-        if self.children.len() == 0 {
+        if self.children.is_empty() {
             self.children_list_state.select(None);
             return;
         }
@@ -59,7 +75,7 @@ impl<'a> TaskEdit<'a> {
     }
 
     pub fn previous(&mut self) {
-        if self.children.len() == 0 {
+        if self.children.is_empty() {
             self.children_list_state.select(None);
             return;
         }
@@ -83,12 +99,16 @@ impl<'a> TaskEdit<'a> {
         self.content.set_cursor_style(default_style);
         self.description.set_cursor_style(default_style);
         self.due_string.set_cursor_style(default_style);
+        self.labels.set_cursor_style(default_style);
 
         match self.currently_editing {
             CurrentlyEditing::Content => self.content.set_cursor_style(active_style),
             CurrentlyEditing::Description => self.description.set_cursor_style(active_style),
             CurrentlyEditing::DueString => self.due_string.set_cursor_style(active_style),
-            CurrentlyEditing::ChildTasks => {}
+            CurrentlyEditing::Labels => self.labels.set_cursor_style(active_style),
+            CurrentlyEditing::Priority
+            | CurrentlyEditing::ChildTasks
+            | CurrentlyEditing::Comments => {}
         }
     }
 }
@@ -99,5 +119,8 @@ pub enum CurrentlyEditing {
     Content,
     Description,
     DueString,
+    Labels,
+    Priority,
     ChildTasks,
+    Comments,
 }
@@ -1,10 +1,20 @@
 extern crate chrono;
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
-use chrono::{Local, NaiveDate};
+use chrono::{Days, Local, NaiveDate};
 use ratatui::widgets::ListState;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::{
+    projects::Project,
+    query::{Context, Query},
+    sections::Section,
+    tree,
+};
+
 #[derive(Debug, Default)]
 pub struct Tasks {
     pub tasks: Vec<Task>,
@@ -12,6 +22,49 @@ pub struct Tasks {
     pub state: ListState,
     pub tasks_with_children: HashMap<String, u16>,
     pub display_tasks: Vec<usize>,
+    /// Sub-projects whose tasks are shown along with `Filter::ProjectId`.
+    pub subproject_ids: HashSet<String>,
+    /// Sections to group a project's tasks under, ordered by `order`.
+    pub sections: Vec<Section>,
+    /// Ids of sections whose tasks are hidden.
+    pub collapsed_sections: HashSet<String>,
+    /// What the list shows, `display_tasks` with section headers in
+    /// between. `state` indexes into this.
+    pub rows: Vec<TaskRow>,
+    /// Whether the project being shown is laid out as a board.
+    pub board: bool,
+    /// Ids of tasks whose subtasks are shown in the list.
+    pub expanded_tasks: HashSet<String>,
+    /// For queries that name projects.
+    pub projects: Vec<Project>,
+    /// `Filter::Query` parsed, when it parses.
+    query: Option<Query>,
+}
+
+/// A line of the task list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskRow {
+    /// Index into `Tasks::sections`.
+    Section(usize),
+    /// Index into `Tasks::tasks`.
+    Task(usize),
+    /// Heads the tasks due that day in the upcoming view.
+    Day(NaiveDate),
+}
+
+impl TaskRow {
+    pub fn is_header(&self) -> bool {
+        matches!(self, TaskRow::Section(_) | TaskRow::Day(_))
+    }
+}
+
+/// A column of the board, a range of `Tasks::rows`. Every column but the
+/// one for tasks without a section starts with its section header.
+#[derive(Debug, Clone)]
+pub struct Column {
+    /// Index into `Tasks::sections`.
+    pub section: Option<usize>,
+    pub rows: Range<usize>,
 }
 
 #[derive(Debug)]
@@ -24,14 +77,33 @@ impl Tasks {
     pub fn new(items: Vec<Task>) -> Tasks {
         Tasks {
             tasks: items,
-            filter: Filter::Today,
+            filter: Filter::default(),
             state: ListState::default(),
             tasks_with_children: HashMap::new(),
             display_tasks: Vec::new(),
+            subproject_ids: HashSet::new(),
+            sections: Vec::new(),
+            collapsed_sections: HashSet::new(),
+            rows: Vec::new(),
+            board: false,
+            expanded_tasks: HashSet::new(),
+            projects: Vec::new(),
+            query: None,
         }
     }
 
+    /// Keeps `sections` ordered, as the grouping relies on it.
+    pub fn set_sections(&mut self, sections: &[Section]) {
+        self.sections = sections.to_vec();
+        self.sections.sort_by_key(|section| section.order);
+    }
+
+    pub fn set_projects(&mut self, projects: &[Project]) {
+        self.projects = projects.to_vec();
+    }
+
     pub fn find_tasks_with_children(&mut self) {
+        self.tasks_with_children.clear();
         for task in &self.tasks {
             if let Some(parent_id) = &task.parent_id {
                 *self
@@ -64,52 +136,443 @@ impl Tasks {
                 });
             }
         }
+        self.build_rows();
     }
 
+    pub fn matches_filter(&self, task: &Task) -> bool {
+        match &self.filter {
+            Filter::ProjectId(project_id) => {
+                task.project_id == *project_id || self.subproject_ids.contains(&task.project_id)
+            }
+            Filter::Label(name) => task.labels.contains(name),
+            Filter::Date(date) => task.due.as_ref().is_some_and(|due| due.date == *date),
+            Filter::Upcoming { days } => {
+                let today = Local::now().date_naive();
+                let end = today
+                    .checked_add_days(Days::new(u64::from(*days)))
+                    .unwrap_or(NaiveDate::MAX);
+                task.due
+                    .as_ref()
+                    .is_some_and(|due| due.date >= today && due.date < end)
+            }
+            Filter::Query(_) => self.query.as_ref().is_some_and(|query| {
+                let context = Context {
+                    today: Local::now().date_naive(),
+                    projects: &self.projects,
+                    sections: &self.sections,
+                };
+                query.matches(task, &context)
+            }),
+        }
+    }
+
+    /// Lists the tasks matching the filter. A subtask is only listed on its
+    /// own when its parent isn't, otherwise it shows up under the parent.
     pub fn filter_task_list(&mut self) {
         self.state = ListState::default();
-        self.display_tasks = Vec::new();
+        self.find_tasks_with_children();
+        self.query = match &self.filter {
+            Filter::Query(text) => Query::parse(text).ok(),
+            _ => None,
+        };
+
+        let matching: Vec<bool> = self
+            .tasks
+            .iter()
+            .map(|task| self.matches_filter(task))
+            .collect();
+        let positions: HashMap<&str, usize> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(index, task)| (task.id.as_str(), index))
+            .collect();
+
+        let mut display_tasks = Vec::new();
         for (index, task) in self.tasks.iter().enumerate() {
-            if let Some(_) = task.parent_id {
+            let parent_listed = task
+                .parent_id
+                .as_deref()
+                .and_then(|parent_id| positions.get(parent_id))
+                .is_some_and(|parent| matching[*parent]);
+            if matching[index] && !parent_listed {
+                display_tasks.push(index);
+            }
+        }
+        self.display_tasks = display_tasks;
+        self.build_rows();
+    }
+
+    /// In a project view tasks are grouped under their section, the ones
+    /// without a section first, and in the upcoming view under their day.
+    /// Other views are a flat list. Outside the board, expanded tasks are
+    /// followed by their subtasks.
+    fn build_rows(&mut self) {
+        if let Filter::Upcoming { days } = self.filter {
+            self.build_agenda(days);
+            return;
+        }
+        let Filter::ProjectId(project_id) = &self.filter else {
+            let mut rows = Vec::new();
+            for index in &self.display_tasks {
+                self.push_task_rows(&mut rows, *index);
+            }
+            self.rows = rows;
+            return;
+        };
+
+        let sections: Vec<usize> = (0..self.sections.len())
+            .filter(|i| {
+                let section_project = &self.sections[*i].project_id;
+                section_project == project_id || self.subproject_ids.contains(section_project)
+            })
+            .collect();
+        let section_of = |index: usize| {
+            let section_id = self.tasks[index].section_id.as_ref()?;
+            sections
+                .iter()
+                .copied()
+                .find(|section| self.sections[*section].id == *section_id)
+        };
+
+        let mut rows = Vec::new();
+        for index in &self.display_tasks {
+            if section_of(*index).is_none() {
+                self.push_task_rows(&mut rows, *index);
+            }
+        }
+        for section in &sections {
+            rows.push(TaskRow::Section(*section));
+            if !self.board
+                && self
+                    .collapsed_sections
+                    .contains(&self.sections[*section].id)
+            {
                 continue;
             }
-            match &self.filter {
-                Filter::All => {
-                    self.display_tasks.push(index);
-                }
-                Filter::Today => {
-                    let today = Local::now().date_naive();
-                    if let Some(due) = &task.due {
-                        if due.date == today {
-                            self.display_tasks.push(index);
-                        }
-                    }
-                }
-                Filter::ProjectId(project_id) => {
-                    if task.project_id == *project_id {
-                        self.display_tasks.push(index);
-                    }
-                }
-                Filter::Overdue => {
-                    let today = Local::now().date_naive();
-                    if let Some(due) = &task.due {
-                        if due.date < today {
-                            self.display_tasks.push(index);
-                        }
-                    }
+            for index in &self.display_tasks {
+                if section_of(*index) == Some(*section) {
+                    self.push_task_rows(&mut rows, *index);
                 }
             }
         }
+        self.rows = rows;
+    }
+
+    /// A header for each of the coming `days`, followed by the tasks due
+    /// that day. Tasks with a time come first, in order of time.
+    fn build_agenda(&mut self, days: u32) {
+        let today = Local::now().date_naive();
+        let mut rows = Vec::new();
+        for date in today.iter_days().take(days as usize) {
+            rows.push(TaskRow::Day(date));
+            let mut due: Vec<usize> = self
+                .display_tasks
+                .iter()
+                .copied()
+                .filter(|index| {
+                    self.tasks[*index]
+                        .due
+                        .as_ref()
+                        .is_some_and(|due| due.date == date)
+                })
+                .collect();
+            due.sort_by_key(|index| {
+                let datetime = self.tasks[*index]
+                    .due
+                    .as_ref()
+                    .and_then(|due| due.datetime.clone());
+                (datetime.is_none(), datetime)
+            });
+            for index in due {
+                self.push_task_rows(&mut rows, index);
+            }
+        }
+        self.rows = rows;
+    }
+
+    /// Number of listed tasks due on `date`.
+    pub fn day_len(&self, date: NaiveDate) -> usize {
+        self.display_tasks
+            .iter()
+            .filter(|index| {
+                self.tasks[**index]
+                    .due
+                    .as_ref()
+                    .is_some_and(|due| due.date == date)
+            })
+            .count()
+    }
+
+    /// Pushes the task and, when it is expanded, its subtasks in order.
+    fn push_task_rows(&self, rows: &mut Vec<TaskRow>, index: usize) {
+        rows.push(TaskRow::Task(index));
+        let task = &self.tasks[index];
+        if self.board
+            || !self.expanded_tasks.contains(&task.id)
+            || tree::in_cycle(&self.tasks, task)
+        {
+            return;
+        }
+        for child in self.children(&task.id) {
+            self.push_task_rows(rows, child);
+        }
+    }
+
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.tasks.iter().position(|task| task.id == id)
+    }
+
+    /// Indices of the direct subtasks of `id`, ordered by `order`.
+    pub fn children(&self, id: &str) -> Vec<usize> {
+        let mut children: Vec<usize> = (0..self.tasks.len())
+            .filter(|index| self.tasks[*index].parent_id.as_deref() == Some(id))
+            .collect();
+        children.sort_by_key(|index| self.tasks[*index].order);
+        children
+    }
+
+    /// How far below its listed ancestor the task is nested.
+    pub fn depth(&self, task: &Task) -> usize {
+        let is_listed = |id: &str| self.display_tasks.iter().any(|i| self.tasks[*i].id == id);
+        let mut depth = 0;
+        let mut current = task;
+        for parent in tree::ancestors(&self.tasks, task) {
+            if is_listed(&current.id) {
+                break;
+            }
+            depth += 1;
+            current = parent;
+        }
+        depth
+    }
+
+    /// Shows or hides the subtasks of the highlighted task. Returns `false`
+    /// when there was nothing to fold, e.g. on a task without subtasks.
+    pub fn set_task_expanded(&mut self, expanded: bool) -> bool {
+        let Some(index) = self.selected_index() else {
+            return false;
+        };
+        let id = self.tasks[index].id.clone();
+        let has_children = self
+            .tasks_with_children
+            .get(&id)
+            .is_some_and(|count| *count > 0);
+        if !has_children || self.expanded_tasks.contains(&id) == expanded {
+            return false;
+        }
+        if expanded {
+            self.expanded_tasks.insert(id.clone());
+        } else {
+            self.expanded_tasks.remove(&id);
+        }
+        self.build_rows();
+        self.select_id(Some(&id));
+        true
+    }
+
+    /// Moves the cursor to the parent of the highlighted subtask.
+    pub fn select_parent(&mut self) -> bool {
+        let Some(parent_id) = self
+            .selected_index()
+            .and_then(|index| self.tasks[index].parent_id.clone())
+        else {
+            return false;
+        };
+        let listed = self
+            .rows
+            .iter()
+            .any(|row| matches!(row, TaskRow::Task(index) if self.tasks[*index].id == parent_id));
+        if listed {
+            self.select_id(Some(&parent_id));
+        }
+        listed
+    }
+
+    pub fn is_board(&self) -> bool {
+        self.board && matches!(self.filter, Filter::ProjectId(_))
+    }
+
+    pub fn columns(&self) -> Vec<Column> {
+        let is_header = |row: &TaskRow| matches!(row, TaskRow::Section(_));
+        let mut columns = Vec::new();
+
+        let first_header = self
+            .rows
+            .iter()
+            .position(is_header)
+            .unwrap_or(self.rows.len());
+        if first_header > 0 {
+            columns.push(Column {
+                section: None,
+                rows: 0..first_header,
+            });
+        }
+        for (i, row) in self.rows.iter().enumerate() {
+            if let TaskRow::Section(section) = row {
+                let end = self.rows[i + 1..]
+                    .iter()
+                    .position(is_header)
+                    .map_or(self.rows.len(), |next| i + 1 + next);
+                columns.push(Column {
+                    section: Some(*section),
+                    rows: i..end,
+                });
+            }
+        }
+        columns
+    }
+
+    pub fn selected_column(&self) -> Option<usize> {
+        let selected = self.state.selected()?;
+        self.columns()
+            .iter()
+            .position(|column| column.rows.contains(&selected))
+    }
+
+    /// Moves the cursor to the next or previous task of the current column.
+    pub fn move_in_column(&mut self, forward: bool) {
+        let columns = self.columns();
+        let Some(column) = self.selected_column().and_then(|i| columns.get(i)) else {
+            self.select_column(&columns, 0, 0);
+            return;
+        };
+        let tasks = self.column_tasks(column);
+        let position = self
+            .state
+            .selected()
+            .and_then(|selected| tasks.iter().position(|row| *row == selected));
+        let target = match position {
+            Some(position) if forward => (position + 1).min(tasks.len() - 1),
+            Some(position) => position.saturating_sub(1),
+            None => 0,
+        };
+        if let Some(row) = tasks.get(target) {
+            self.state.select(Some(*row));
+        }
+    }
+
+    /// Moves the cursor to the neighbouring column, keeping its height.
+    pub fn move_to_column(&mut self, forward: bool) {
+        let columns = self.columns();
+        let Some(current) = self.selected_column() else {
+            self.select_column(&columns, 0, 0);
+            return;
+        };
+        let target = if forward {
+            current + 1
+        } else if current > 0 {
+            current - 1
+        } else {
+            return;
+        };
+        let position = self
+            .state
+            .selected()
+            .and_then(|selected| {
+                self.column_tasks(&columns[current])
+                    .iter()
+                    .position(|row| *row == selected)
+            })
+            .unwrap_or(0);
+        self.select_column(&columns, target, position);
+    }
+
+    /// Selects a task of the column, or its header when it is empty.
+    fn select_column(&mut self, columns: &[Column], column: usize, position: usize) {
+        let Some(column) = columns.get(column) else {
+            return;
+        };
+        let tasks = self.column_tasks(column);
+        let row = match tasks.len() {
+            0 => column.rows.start,
+            len => tasks[position.min(len - 1)],
+        };
+        self.state.select(Some(row));
+    }
+
+    /// Rows of the tasks in the column.
+    pub fn column_tasks(&self, column: &Column) -> Vec<usize> {
+        column
+            .rows
+            .clone()
+            .filter(|row| matches!(self.rows[*row], TaskRow::Task(_)))
+            .collect()
+    }
+
+    /// Number of listed tasks in a section, collapsed or not.
+    pub fn section_len(&self, section: &Section) -> usize {
+        self.display_tasks
+            .iter()
+            .filter(|index| self.tasks[**index].section_id.as_ref() == Some(&section.id))
+            .count()
+    }
+
+    /// The highlighted section header, or the section of the highlighted task.
+    pub fn selected_section(&self) -> Option<&Section> {
+        match self.rows.get(self.state.selected()?)? {
+            TaskRow::Section(section) => self.sections.get(*section),
+            TaskRow::Day(_) => None,
+            TaskRow::Task(index) => {
+                let section_id = self.tasks[*index].section_id.as_ref()?;
+                self.sections
+                    .iter()
+                    .find(|section| section.id == *section_id)
+            }
+        }
+    }
+
+    /// Shows or hides the tasks of the section the cursor is in, leaving
+    /// the cursor on its header.
+    pub fn set_section_collapsed(&mut self, collapsed: bool) {
+        let Some(id) = self.selected_section().map(|section| section.id.clone()) else {
+            return;
+        };
+        if collapsed {
+            self.collapsed_sections.insert(id.clone());
+        } else {
+            self.collapsed_sections.remove(&id);
+        }
+        self.build_rows();
+        let header = self.rows.iter().position(|row| match row {
+            TaskRow::Section(section) => self.sections[*section].id == id,
+            TaskRow::Task(_) | TaskRow::Day(_) => false,
+        });
+        self.state.select(header);
+    }
+
+    /// Moves to the next section or day header.
+    pub fn next_section(&mut self) {
+        let current = self.state.selected();
+        let next = self
+            .rows
+            .iter()
+            .enumerate()
+            .position(|(i, row)| row.is_header() && current.is_none_or(|current| i > current));
+        if next.is_some() {
+            self.state.select(next);
+        }
+    }
+
+    pub fn previous_section(&mut self) {
+        let Some(current) = self.state.selected() else {
+            return;
+        };
+        let previous = self.rows[..current.min(self.rows.len())]
+            .iter()
+            .rposition(TaskRow::is_header);
+        // before the first header are the tasks without a section
+        self.state.select(previous.or(Some(0)));
     }
 
     pub fn next(&mut self) {
-        if self.display_tasks.len() == 0 {
+        if self.rows.is_empty() {
             self.state.select(None);
             return;
         }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.display_tasks.len() - 1 {
+                if i >= self.rows.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -121,14 +584,14 @@ impl Tasks {
     }
 
     pub fn previous(&mut self) {
-        if self.display_tasks.len() == 0 {
+        if self.rows.is_empty() {
             self.state.select(None);
             return;
         }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.display_tasks.len() - 1
+                    self.rows.len() - 1
                 } else {
                     i - 1
                 }
@@ -138,6 +601,44 @@ impl Tasks {
         self.state.select(Some(i));
     }
 
+    /// Index into `tasks` of the highlighted task, `None` on a header.
+    pub fn selected_index(&self) -> Option<usize> {
+        match self.rows.get(self.state.selected()?)? {
+            TaskRow::Task(index) => Some(*index),
+            TaskRow::Section(_) | TaskRow::Day(_) => None,
+        }
+    }
+
+    /// Id of the highlighted section header.
+    pub fn selected_header_id(&self) -> Option<String> {
+        match self.rows.get(self.state.selected()?)? {
+            TaskRow::Section(section) => Some(self.sections[*section].id.clone()),
+            TaskRow::Task(_) | TaskRow::Day(_) => None,
+        }
+    }
+
+    pub fn select_section(&mut self, id: &str) {
+        let index = self.rows.iter().position(
+            |row| matches!(row, TaskRow::Section(section) if self.sections[*section].id == id),
+        );
+        self.state.select(index);
+    }
+
+    pub fn selected_id(&self) -> Option<String> {
+        self.selected_index()
+            .and_then(|index| self.tasks.get(index))
+            .map(|task| task.id.clone())
+    }
+
+    pub fn select_id(&mut self, id: Option<&str>) {
+        let index = id.and_then(|id| {
+            self.rows
+                .iter()
+                .position(|row| matches!(row, TaskRow::Task(index) if self.tasks[*index].id == id))
+        });
+        self.state.select(index);
+    }
+
     pub fn unselect(&mut self) {
         let offset = self.state.offset();
         self.state.select(None);
@@ -145,13 +646,26 @@ impl Tasks {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone)]
 pub enum Filter {
-    #[default]
-    All,
-    Today,
-    Overdue,
     ProjectId(String),
+    /// Tasks with the label of this name.
+    Label(String),
+    /// A Todoist filter query, see `query::Query`.
+    Query(String),
+    /// Tasks due in the coming days, grouped by day.
+    Upcoming {
+        days: u32,
+    },
+    /// Tasks due on the day picked in the calendar.
+    Date(NaiveDate),
+}
+
+/// Today's tasks, what there is before anything else is picked.
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::Query("today".to_string())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -169,15 +683,72 @@ pub struct Task {
     pub due: Option<Due>,
     pub url: String,
     pub comment_count: u16,
-    #[serde(skip_serializing)]
+    #[serde(skip_serializing, default)]
     pub created_at: String,
-    #[serde(skip_serializing)]
+    #[serde(skip_serializing, default)]
     pub creator_id: String,
     pub assignee_id: Option<String>,
     pub assigner_id: Option<String>,
     pub duration: Option<Duration>,
 }
 
+impl Task {
+    /// Builds a task locally from the JSON used to create it, for when
+    /// Todoist hasn't answered (or can't) yet.
+    pub fn from_json(id: String, json: &serde_json::Value) -> Task {
+        let string_field = |key: &str| json[key].as_str().map(|s| s.to_string());
+
+        Task {
+            url: String::new(),
+            id,
+            project_id: string_field("project_id").unwrap_or_default(),
+            section_id: string_field("section_id"),
+            content: string_field("content").unwrap_or_default(),
+            description: string_field("description").unwrap_or_default(),
+            is_completed: false,
+            labels: serde_json::from_value(json["labels"].clone()).unwrap_or_default(),
+            parent_id: string_field("parent_id"),
+            order: 0,
+            priority: json["priority"].as_u64().unwrap_or(1) as u8,
+            due: Due::from_json(json),
+            comment_count: 0,
+            created_at: Local::now().to_rfc3339(),
+            creator_id: String::new(),
+            assignee_id: string_field("assignee_id"),
+            assigner_id: None,
+            duration: None,
+        }
+    }
+}
+
+/// Removes a task along with its subtasks, as Todoist does when one is
+/// completed or deleted.
+pub fn remove_task(tasks: &mut Vec<Task>, id: &str) {
+    let mut removed = tree::descendants(tasks, id);
+    removed.insert(id.to_string());
+    tasks.retain(|task| !removed.contains(&task.id));
+}
+
+/// What a priority is called, the API counts the other way round: 4 is P1.
+pub fn priority_name(priority: u8) -> &'static str {
+    match priority {
+        1 => "P4",
+        2 => "P3",
+        3 => "P2",
+        4 => "P1",
+        _ => "",
+    }
+}
+
+/// The priority a step more (`raise`) or less urgent, within P1 to P4.
+pub fn bump_priority(priority: u8, raise: bool) -> u8 {
+    if raise {
+        (priority + 1).clamp(1, 4)
+    } else {
+        priority.saturating_sub(1).clamp(1, 4)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Due {
     pub string: String,
@@ -188,6 +759,47 @@ pub struct Due {
     pub timezone: Option<String>,
 }
 
+impl Due {
+    /// Understands `due_datetime`, `due_date` and a small subset of
+    /// `due_string`; Todoist does the real natural language parsing.
+    pub fn from_json(json: &serde_json::Value) -> Option<Due> {
+        let today = Local::now().date_naive();
+        if let Some(datetime) = json["due_datetime"].as_str() {
+            let date = NaiveDate::parse_from_str(datetime.get(..10)?, "%Y-%m-%d").ok()?;
+            return Some(Due {
+                string: datetime.to_string(),
+                date,
+                is_recurring: false,
+                datetime: Some(datetime.to_string()),
+                timezone: None,
+            });
+        }
+        let (string, date) = if let Some(date) = json["due_date"].as_str() {
+            (
+                date.to_string(),
+                NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?,
+            )
+        } else {
+            let string = json["due_string"].as_str()?.trim().to_string();
+            let date = match string.to_lowercase().as_str() {
+                "" | "no date" => return None,
+                "today" => today,
+                "tomorrow" => today.checked_add_days(Days::new(1))?,
+                other => NaiveDate::parse_from_str(other, "%Y-%m-%d").ok()?,
+            };
+            (string, date)
+        };
+
+        Some(Due {
+            string,
+            date,
+            is_recurring: false,
+            datetime: None,
+            timezone: None,
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Duration {
     amount: u32,
@@ -0,0 +1,123 @@
+use std::{collections::HashSet, iter};
+
+use crate::{projects::Project, tasks::Task};
+
+/// Tasks and projects, which nest under others of their kind.
+pub trait Nested {
+    fn id(&self) -> &str;
+    fn parent_id(&self) -> Option<&str>;
+}
+
+impl Nested for Task {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn parent_id(&self) -> Option<&str> {
+        self.parent_id.as_deref()
+    }
+}
+
+impl Nested for Project {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn parent_id(&self) -> Option<&str> {
+        self.parent_id.as_deref()
+    }
+}
+
+/// The parent of `item` among `items`, then its parent and so on. It stops
+/// after as many steps as there are items, as a parent cycle in bad data
+/// would otherwise never end.
+pub fn ancestors<'a, T: Nested>(items: &'a [T], item: &T) -> impl Iterator<Item = &'a T> {
+    let parent = move |item: &T| {
+        let parent_id = item.parent_id()?;
+        items.iter().find(|other| other.id() == parent_id)
+    };
+    iter::successors(parent(item), move |item| parent(item)).take(items.len())
+}
+
+/// Whether `item` is its own ancestor, so walking down from it never ends.
+pub fn in_cycle<T: Nested>(items: &[T], item: &T) -> bool {
+    ancestors(items, item).any(|ancestor| ancestor.id() == item.id())
+}
+
+/// The ids of the children of `id` among `items`, their children and so on.
+/// A parent cycle through `id` brings `id` itself along.
+pub fn descendants<T: Nested>(items: &[T], id: &str) -> HashSet<String> {
+    let mut descendants = HashSet::new();
+    let mut queue = vec![id.to_string()];
+    while let Some(parent_id) = queue.pop() {
+        for item in items {
+            if item.parent_id() == Some(parent_id.as_str())
+                && descendants.insert(item.id().to_string())
+            {
+                queue.push(item.id().to_string());
+            }
+        }
+    }
+    descendants
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn project(id: &str, parent_id: Option<&str>) -> Project {
+        Project::from_json(
+            id.to_string(),
+            &json!({ "name": id, "parent_id": parent_id }),
+        )
+    }
+
+    fn ids<'a>(projects: impl Iterator<Item = &'a Project>) -> Vec<&'a str> {
+        projects.map(|project| project.id.as_str()).collect()
+    }
+
+    #[test]
+    fn walks_up_to_the_root() {
+        let projects = [
+            project("a", None),
+            project("b", Some("a")),
+            project("c", Some("b")),
+        ];
+        assert_eq!(ids(ancestors(&projects, &projects[2])), ["b", "a"]);
+        assert_eq!(ids(ancestors(&projects, &projects[0])), Vec::<&str>::new());
+        assert!(!in_cycle(&projects, &projects[2]));
+    }
+
+    #[test]
+    fn walks_down_to_the_leaves() {
+        let projects = [
+            project("c", Some("b")),
+            project("b", Some("a")),
+            project("a", None),
+            project("d", Some("a")),
+        ];
+        let mut below_a: Vec<String> = descendants(&projects, "a").into_iter().collect();
+        below_a.sort();
+        assert_eq!(below_a, ["b", "c", "d"]);
+        assert!(descendants(&projects, "c").is_empty());
+    }
+
+    #[test]
+    fn stops_on_a_missing_parent() {
+        let projects = [project("a", Some("gone")), project("b", Some("a"))];
+        assert_eq!(ids(ancestors(&projects, &projects[1])), ["a"]);
+    }
+
+    #[test]
+    fn ends_on_a_cycle() {
+        let projects = [project("a", Some("b")), project("b", Some("a"))];
+        assert_eq!(ancestors(&projects, &projects[0]).count(), 2);
+        assert_eq!(descendants(&projects, "a").len(), 2);
+        assert!(in_cycle(&projects, &projects[0]));
+
+        let projects = [project("a", Some("a"))];
+        assert!(in_cycle(&projects, &projects[0]));
+    }
+}
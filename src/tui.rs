@@ -1,18 +1,31 @@
 use std::io::{self, stdout, Stdout};
 
+use chrono::Local;
 use crossterm::{execute, terminal::*};
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, HighlightSpacing, List, ListItem, Paragraph},
 };
 
+mod board;
+mod calendar;
+mod comments;
+mod confirm;
 mod error;
 mod help;
+mod import;
+mod loading;
 mod new_task;
+mod project_editor;
+mod prompt;
 mod task_editor;
 mod utils;
 
-use crate::{tasks::Filter, App, CurrentFocus};
+use crate::{
+    project_edit::COLORS,
+    tasks::{Filter, TaskRow},
+    App, CurrentFocus,
+};
 
 pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
@@ -28,6 +41,22 @@ pub fn restore() -> std::io::Result<()> {
     Ok(())
 }
 
+/// What the tasks being shown are, e.g. a project's name or `@waiting`.
+pub fn view_title(app: &App) -> String {
+    match &app.tasks.filter {
+        Filter::ProjectId(project_id) => app
+            .projects
+            .projects
+            .iter()
+            .find(|project| project.id == *project_id)
+            .map_or("Tasks".to_string(), |project| project.name.clone()),
+        Filter::Label(name) => format!("@{}", name),
+        Filter::Upcoming { days } => format!("Upcoming, next {} days", days),
+        Filter::Date(date) => format!("Due {}", utils::format_date(*date)),
+        Filter::Query(query) => app.filters.name_of(query).unwrap_or(query).to_string(),
+    }
+}
+
 pub fn ui(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -55,9 +84,26 @@ pub fn ui(f: &mut Frame, app: &mut App) {
 
     f.render_widget(title, chunks[0]);
 
+    // filters and labels go under the projects, when there are any
+    let pane_height = |len: usize| match len {
+        0 => 0,
+        len => (len as u16 + 2).min(inner_layout[0].height / 4),
+    };
+    let filters_height = pane_height(app.filters.filters.len());
+    let labels_height = pane_height(app.labels.labels.len());
+    let side_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(filters_height),
+            Constraint::Length(labels_height),
+        ])
+        .split(inner_layout[0]);
+
     let mut list_items = Vec::<ListItem>::new();
 
-    for project in &app.projects.projects {
+    for index in &app.projects.display_projects {
+        let project = &app.projects.projects[*index];
         let color = if let Some(selected_project) = &app.projects.selected_project {
             if project.id == *selected_project {
                 Color::Indexed(214)
@@ -68,10 +114,19 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             Color::Yellow
         };
 
-        list_items.push(ListItem::new(Line::from(Span::styled(
-            format!("{}", project.name),
-            Style::default().fg(color),
-        ))));
+        let marker = if !app.projects.has_children(&project.id) {
+            "  "
+        } else if app.projects.collapsed.contains(&project.id) {
+            "▸ "
+        } else {
+            "▾ "
+        };
+        let indent = "  ".repeat(app.projects.depth(project));
+
+        list_items.push(ListItem::new(Line::from(vec![
+            Span::raw(format!("{}{}", indent, marker)),
+            Span::styled(project.name.to_string(), Style::default().fg(color)),
+        ])));
     }
 
     let my_projects_block = Block::default()
@@ -87,13 +142,83 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
         .highlight_symbol(">")
         .highlight_spacing(HighlightSpacing::Always);
-    f.render_stateful_widget(list, inner_layout[0], &mut app.projects.state);
+    f.render_stateful_widget(list, side_layout[0], &mut app.projects.state);
+
+    if filters_height > 0 {
+        let filter_items: Vec<ListItem> = app
+            .filters
+            .filters
+            .iter()
+            .map(|filter| {
+                let mut spans = vec![Span::styled(
+                    filter.name.clone(),
+                    Style::default().fg(Color::Cyan),
+                )];
+                if let Some(key) = filter.key {
+                    spans.push(Span::styled(
+                        format!(" ({})", key),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let filters_block = Block::default()
+            .title(" Filters ".bold())
+            .borders(Borders::ALL)
+            .fg(match app.current_focus {
+                CurrentFocus::Filters => Color::Indexed(47),
+                _ => Color::White,
+            });
+
+        let list = List::new(filter_items)
+            .block(filters_block)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        f.render_stateful_widget(list, side_layout[1], &mut app.filters.state);
+    }
+
+    if labels_height > 0 {
+        let label_items: Vec<ListItem> = app
+            .labels
+            .labels
+            .iter()
+            .map(|label| {
+                let color = COLORS
+                    .iter()
+                    .find(|(name, _)| *name == label.color)
+                    .map_or(Color::Magenta, |(_, color)| *color);
+                ListItem::new(Line::from(Span::styled(
+                    format!("@{}", label.name),
+                    Style::default().fg(color),
+                )))
+            })
+            .collect();
 
-    let task_title = match app.tasks.filter {
-        Filter::All => " All ",
-        Filter::Today => " Today ",
-        Filter::ProjectId(_) => " Tasks ",
-        Filter::Overdue => " Overdue ",
+        let labels_block = Block::default()
+            .title(" Labels ".bold())
+            .borders(Borders::ALL)
+            .fg(match app.current_focus {
+                CurrentFocus::Labels => Color::Indexed(47),
+                _ => Color::White,
+            });
+
+        let list = List::new(label_items)
+            .block(labels_block)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        f.render_stateful_widget(list, side_layout[2], &mut app.labels.state);
+    }
+
+    let task_title = match &app.tasks.filter {
+        Filter::ProjectId(_) if app.projects.include_subprojects => {
+            " Tasks, with sub-projects ".to_string()
+        }
+        Filter::ProjectId(_) => " Tasks ".to_string(),
+        _ => format!(" {} ", view_title(app)),
     };
 
     let instructions = Line::from(vec![" For help, press ".into(), "h ".blue().bold()]);
@@ -107,37 +232,158 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             _ => Color::White,
         });
 
-    let mut task_list_item = Vec::new();
-    let task_list_width = inner_layout[1].width as usize;
-    for i in &app.tasks.display_tasks {
-        let task = &app.tasks.tasks[*i];
-        let children: u16 = *app.tasks.tasks_with_children.get(&task.id).unwrap_or(&0);
-        task_list_item.push(utils::generate_list_item(
-            &task.content,
-            &task.due,
-            task.priority,
-            task.is_completed,
-            children,
-            task_list_width - 4,
-        ))
-    }
-
-    let task_list = List::new(task_list_item)
-        .block(tasks_block)
-        .highlight_style(
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .add_modifier(Modifier::REVERSED)
-                .fg(Color::Cyan),
-        )
-        .highlight_symbol(">")
-        .highlight_spacing(HighlightSpacing::Always);
+    // the calendar sits above the tasks
+    let tasks_area = if app.show_calendar {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(calendar::height(app)),
+                Constraint::Min(1),
+            ])
+            .split(inner_layout[1]);
+        calendar::calendar(f, app, split[0]);
+        split[1]
+    } else {
+        inner_layout[1]
+    };
 
-    f.render_stateful_widget(task_list, inner_layout[1], &mut app.tasks.state);
+    if app.tasks.is_board() {
+        board::board(f, app, tasks_area, tasks_block);
+    } else {
+        let mut task_list_item = Vec::new();
+        let task_list_width = tasks_area.width as usize;
+        for row in &app.tasks.rows {
+            let i = match row {
+                TaskRow::Task(i) => i,
+                TaskRow::Section(section) => {
+                    let section = &app.tasks.sections[*section];
+                    let marker = if app.tasks.collapsed_sections.contains(&section.id) {
+                        "▸"
+                    } else {
+                        "▾"
+                    };
+                    task_list_item.push(ListItem::new(Line::from(Span::styled(
+                        format!(
+                            "{} {} ({})",
+                            marker,
+                            section.name,
+                            app.tasks.section_len(section)
+                        ),
+                        Style::default().fg(Color::Magenta).bold(),
+                    ))));
+                    continue;
+                }
+                TaskRow::Day(date) => {
+                    let today = Local::now().date_naive();
+                    let relative = if *date == today {
+                        " · Today"
+                    } else if date.pred_opt() == Some(today) {
+                        " · Tomorrow"
+                    } else {
+                        ""
+                    };
+                    task_list_item.push(ListItem::new(Line::from(Span::styled(
+                        format!(
+                            "{} {}{} ({})",
+                            date.format("%A"),
+                            utils::format_date(*date),
+                            relative,
+                            app.tasks.day_len(*date)
+                        ),
+                        Style::default().fg(Color::Magenta).bold(),
+                    ))));
+                    continue;
+                }
+            };
+            let task = &app.tasks.tasks[*i];
+            let marker = if !app.tasks.tasks_with_children.contains_key(&task.id) {
+                " "
+            } else if app.tasks.expanded_tasks.contains(&task.id) {
+                "▾"
+            } else {
+                "▸"
+            };
+            task_list_item.push(utils::generate_list_item(
+                task,
+                app.tasks.depth(task),
+                marker,
+                task_list_width - 4,
+            ))
+        }
+
+        let task_list = List::new(task_list_item)
+            .block(tasks_block)
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(Color::Cyan),
+            )
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+
+        f.render_stateful_widget(task_list, tasks_area, &mut app.tasks.state);
+    }
+
+    // nothing to show until the first fetch comes back
+    if app.is_syncing && app.last_synced.is_none() {
+        loading::loading(f, inner_layout[1]);
+    }
+
+    let status = match (&app.last_synced, app.is_stale) {
+        (None, _) if app.is_syncing => {
+            Span::styled(" Loading... ", Style::default().fg(Color::DarkGray))
+        }
+        _ if app.is_syncing => Span::styled(" Syncing... ", Style::default().fg(Color::DarkGray)),
+        (Some(time), true) => Span::styled(
+            format!(
+                " Offline, showing data cached on {} at {} ",
+                utils::format_date(time.date_naive()),
+                time.format("%H:%M")
+            ),
+            Style::default().fg(Color::Yellow),
+        ),
+        (Some(time), false) => Span::styled(
+            format!(" Synced at {} ", time.format("%H:%M")),
+            Style::default().fg(Color::DarkGray),
+        ),
+        (None, _) => Span::styled(
+            " Not synced yet, press R to try again ",
+            Style::default().fg(Color::Yellow),
+        ),
+    };
+
+    let mut status_spans = vec![status];
+    let pending_changes = app.outbox.pending_count();
+    if pending_changes > 0 {
+        status_spans.push(Span::styled(
+            format!(
+                " {} change{} waiting to sync ",
+                pending_changes,
+                if pending_changes == 1 { "" } else { "s" }
+            ),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
+    if let Some(notice) = &app.notice {
+        status_spans.push(Span::styled(
+            format!(" {} ", notice),
+            Style::default().fg(Color::Indexed(47)),
+        ));
+    }
+
+    let status_line = Paragraph::new(Line::from(status_spans)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .style(Style::default()),
+    );
+
+    f.render_widget(status_line, chunks[2]);
 
     // help popup
     if app.show_help {
-        help::help(f);
+        help::help(f, app);
     }
 
     if app.show_task_editor {
@@ -148,6 +394,26 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         new_task::editor(f, app)
     }
 
+    if app.show_project_editor {
+        project_editor::editor(f, app)
+    }
+
+    if app.show_comments {
+        comments::comments(f, app)
+    }
+
+    if app.import.is_some() {
+        import::preview(f, app)
+    }
+
+    if let Some(confirmation) = &app.confirmation {
+        confirm::confirm(f, confirmation);
+    }
+
+    if let Some(prompt) = &mut app.prompt {
+        prompt::prompt(f, prompt);
+    }
+
     if app.show_error {
         error::render_error_modal(f, app);
     }
@@ -0,0 +1,76 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, HighlightSpacing, List, ListState},
+};
+
+use crate::{tasks::TaskRow, tui::utils, App};
+
+/// Lays the project out as one column per section.
+pub fn board(f: &mut Frame, app: &App, area: Rect, block: Block) {
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let columns = app.tasks.columns();
+    if columns.is_empty() {
+        return;
+    }
+    let selected_column = app.tasks.selected_column();
+    let areas = Layout::horizontal(vec![
+        Constraint::Ratio(1, columns.len() as u32);
+        columns.len()
+    ])
+    .split(inner);
+
+    for (i, column) in columns.iter().enumerate() {
+        let title = match column.section {
+            Some(section) => app.tasks.sections[section].name.clone(),
+            None => "(No section)".to_string(),
+        };
+        let rows = app.tasks.column_tasks(column);
+
+        let column_block = Block::default()
+            .title(format!(" {} ({}) ", title, rows.len()).bold())
+            .borders(Borders::ALL)
+            .fg(if selected_column == Some(i) {
+                Color::Magenta
+            } else {
+                Color::White
+            });
+
+        let width = (areas[i].width as usize).saturating_sub(4);
+        let items: Vec<_> = rows
+            .iter()
+            .filter_map(|row| match app.tasks.rows[*row] {
+                TaskRow::Task(index) => Some(&app.tasks.tasks[index]),
+                TaskRow::Section(_) | TaskRow::Day(_) => None,
+            })
+            .map(|task| {
+                let marker = if app.tasks.tasks_with_children.contains_key(&task.id) {
+                    "⤷"
+                } else {
+                    " "
+                };
+                utils::generate_list_item(task, 0, marker, width)
+            })
+            .collect();
+
+        let mut state = ListState::default().with_selected(
+            app.tasks
+                .state
+                .selected()
+                .and_then(|selected| rows.iter().position(|row| *row == selected)),
+        );
+
+        let list = List::new(items)
+            .block(column_block)
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(Color::Cyan),
+            )
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        f.render_stateful_widget(list, areas[i], &mut state);
+    }
+}
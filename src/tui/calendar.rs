@@ -0,0 +1,74 @@
+use chrono::{Local, NaiveDate};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{App, CurrentFocus};
+
+/// Rows the calendar needs: a line per week, the weekday names and borders.
+pub fn height(app: &App) -> u16 {
+    app.calendar.weeks().len() as u16 + 3
+}
+
+pub fn calendar(f: &mut Frame, app: &App, area: Rect) {
+    let today = Local::now().date_naive();
+    let count = |date: NaiveDate| {
+        app.tasks
+            .tasks
+            .iter()
+            .filter(|task| task.due.as_ref().is_some_and(|due| due.date == date))
+            .count()
+    };
+
+    // every day takes 8 columns, the number right aligned in the first 3
+    let weekdays: String = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"]
+        .iter()
+        .map(|day| format!(" {:<7}", day))
+        .collect();
+    let mut lines = vec![Line::styled(weekdays, Style::default().fg(Color::DarkGray))];
+    for week in app.calendar.weeks() {
+        let mut spans = Vec::new();
+        for day in week {
+            let Some(date) = day else {
+                spans.push(Span::raw("        "));
+                continue;
+            };
+            let tasks = count(date);
+            let mut style = if tasks > 0 && date < today {
+                Style::default().fg(Color::Red)
+            } else if tasks > 0 {
+                Style::default().fg(Color::White)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            if date == today {
+                style = style.bold().underlined();
+            }
+            if date == app.calendar.selected {
+                style = style.reversed();
+            }
+            let tasks = if tasks > 0 {
+                format!("({})", tasks)
+            } else {
+                String::new()
+            };
+            spans.push(Span::styled(
+                format!("{:>3}{:<4}", date.format("%-d"), tasks),
+                style,
+            ));
+            spans.push(Span::raw(" "));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let block = Block::default()
+        .title(format!(" {} ", app.calendar.selected.format("%B %Y")).bold())
+        .borders(Borders::ALL)
+        .fg(match app.current_focus {
+            CurrentFocus::Calendar => Color::Indexed(47),
+            _ => Color::White,
+        });
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,126 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, HighlightSpacing, List, ListItem, Paragraph, Wrap},
+};
+use textwrap::fill;
+
+use crate::{tui::utils, App};
+
+/// The comments of `app.comments`, newest last.
+pub fn comment_list(f: &mut Frame, app: &mut App, area: Rect, focused: bool) {
+    let title = if app.comments.loaded {
+        format!(" Comments ({}) ", app.comments.comments.len())
+    } else {
+        " Comments ".to_string()
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .fg(if focused {
+            Color::Indexed(47)
+        } else {
+            Color::White
+        });
+
+    let placeholder = if let Some(error) = &app.comments.error {
+        Some(format!("Could not load the comments: {}", error))
+    } else if !app.comments.loaded {
+        Some("Loading...".to_string())
+    } else if app.comments.comments.is_empty() {
+        Some("No comments yet, press n to add one".to_string())
+    } else {
+        None
+    };
+    if let Some(placeholder) = placeholder {
+        let paragraph = Paragraph::new(placeholder)
+            .block(block)
+            .style(Style::default().fg(Color::DarkGray))
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let width = (area.width as usize).saturating_sub(4).max(1);
+    // user ids are the same in every project
+    let collaborators = app.collaborators.values().flatten().flatten();
+    let items: Vec<ListItem> = app
+        .comments
+        .comments
+        .iter()
+        .map(|comment| {
+            let posted_at = Span::styled(comment.posted_at(), Style::default().fg(Color::DarkGray));
+            let header = match comment.author(collaborators.clone()) {
+                Some(author) => Line::from(vec![
+                    Span::styled(author.to_string(), Style::default().bold()),
+                    Span::styled(" · ", Style::default().fg(Color::DarkGray)),
+                    posted_at,
+                ]),
+                None => Line::from(posted_at),
+            };
+            let mut lines = vec![header];
+            lines.extend(
+                fill(&comment.content, width)
+                    .lines()
+                    .map(|line| Line::from(line.to_string())),
+            );
+            ListItem::new(lines)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .add_modifier(Modifier::REVERSED)
+                .fg(Color::Cyan),
+        )
+        .highlight_symbol(">")
+        .highlight_spacing(HighlightSpacing::Always);
+
+    f.render_stateful_widget(list, area, &mut app.comments.state);
+}
+
+/// The comments of a project, in a modal of their own.
+pub fn comments(f: &mut Frame, app: &mut App) {
+    let area = utils::centered_rect(
+        Constraint::Percentage(60),
+        Constraint::Percentage(60),
+        f.area(),
+    );
+    f.render_widget(Clear, area);
+
+    let name = match &app.comments.target {
+        Some(target) => {
+            let id = target.param().1;
+            app.projects
+                .projects
+                .iter()
+                .find(|project| project.id == id)
+                .map_or(String::new(), |project| project.name.clone())
+        }
+        None => String::new(),
+    };
+    let instructions = Line::from(vec![
+        " Press ".into(),
+        "n".blue().bold(),
+        " to add, ".into(),
+        "e".blue().bold(),
+        " to edit, ".into(),
+        "x".blue().bold(),
+        " to delete and ".into(),
+        "Esc".blue().bold(),
+        " to close ".into(),
+    ]);
+    let block = Block::default()
+        .title(format!(" {} ", name))
+        .title_bottom(instructions.centered())
+        .borders(Borders::ALL);
+
+    let inner_area = area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    f.render_widget(block, area);
+    comment_list(f, app, inner_area, true);
+}
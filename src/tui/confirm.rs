@@ -0,0 +1,31 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::{confirm::Confirmation, tui::utils};
+
+pub fn confirm(f: &mut Frame, confirmation: &Confirmation) {
+    let answer = Line::from(vec![
+        " Press ".into(),
+        "y".blue().bold(),
+        " to confirm or ".into(),
+        "Esc".blue().bold(),
+        " to cancel ".into(),
+    ]);
+
+    let block = Block::default()
+        .title(" Are you sure? ")
+        .title_bottom(answer.centered())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(confirmation.message.as_str())
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    let area = utils::centered_rect(Constraint::Percentage(50), Constraint::Length(6), f.area());
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
@@ -1,46 +1,134 @@
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Clear, Row, Table},
+    widgets::{Block, Borders, Clear, Row, Table, TableState},
 };
 
-use crate::tui::utils;
+use crate::{tui::utils, App};
 
-pub fn help(f: &mut Frame) {
-    let close_help = Line::from(vec![
+/// The keys, grouped by where they work. Scrolls with j and k when it
+/// doesn't fit.
+pub fn help(f: &mut Frame, app: &mut App) {
+    // the filters with a key, as configured
+    let filter_rows = app.filters.filters.iter().filter_map(|filter| {
+        let key = filter.key?;
+        Some((key.to_string(), filter.name.clone()))
+    });
+    let anywhere = filter_rows.chain(
+        [
+            ("u", "Upcoming tasks, by day"),
+            ("/", "Filter with a query, e.g. p1 & #Work"),
+            ("Q", "Quick add a task on one line"),
+            ("E / I", "Export this view or all tasks, or import a file"),
+            ("p / d", "Sort by priority or date"),
+            ("c", "Show or hide the calendar"),
+            (
+                "r / R",
+                "Fetch changes made elsewhere, or reload everything",
+            ),
+            (
+                "Tab",
+                "Switch between projects, filters, labels, calendar and tasks",
+            ),
+            ("h / q", "Show or hide this help, or quit"),
+        ]
+        .map(|(key, action)| (key.to_string(), action.to_string())),
+    );
+    let sections: [(&str, &[(&str, &str)]); 6] = [
+        (
+            "Tasks",
+            &[
+                ("Enter", "Edit the highlighted task, or fold the section"),
+                ("n", "New task in the project or section, or with the label"),
+                ("x / d", "Complete or delete the highlighted task"),
+                ("+ / -", "Raise or lower the highlighted task's priority"),
+                ("→ / ←", "Expand or collapse subtasks or a section"),
+                ("J / K", "Jump to the next or previous section or day"),
+                ("m", "Move the task to the day highlighted in the calendar"),
+                ("< / >", "Move a task to the previous or next board column"),
+            ],
+        ),
+        (
+            "Sections",
+            &[
+                ("S", "New section in the project being shown"),
+                ("e / x", "Rename or delete the highlighted section"),
+                ("{ / }", "Move the highlighted section up or down"),
+            ],
+        ),
+        (
+            "Projects",
+            &[
+                ("n / +", "Add a task to the highlighted project"),
+                ("N", "New project"),
+                ("e", "Edit the highlighted project"),
+                ("A / x", "Archive or delete the highlighted project"),
+                ("C", "Comments of the highlighted project"),
+                ("s", "Include sub-projects in a project's tasks"),
+                ("→ / ←", "Expand or collapse sub-projects"),
+            ],
+        ),
+        (
+            "Labels",
+            &[("n / +", "New task with the highlighted label")],
+        ),
+        (
+            "Calendar",
+            &[
+                ("←→↑↓ / [ ]", "Move by day, week or month"),
+                ("Enter", "Tasks due on the highlighted day"),
+                ("m", "Move the highlighted task to the highlighted day"),
+            ],
+        ),
+        (
+            "Comments",
+            &[("n / e / x", "Add, edit or delete a comment")],
+        ),
+    ];
+
+    let heading = |title: &str| Row::new(vec![title.to_string()]).style(Style::new().bold().blue());
+    let mut rows = vec![heading("Anywhere")];
+    rows.extend(anywhere.map(|(key, action)| Row::new(vec![key, action])));
+    for (title, keys) in sections {
+        rows.push(Row::new(vec![""]));
+        rows.push(heading(title));
+        rows.extend(
+            keys.iter()
+                .map(|(key, action)| Row::new(vec![*key, *action])),
+        );
+    }
+
+    // borders, the header and the line under it
+    let total_height = rows.len() as u16 + 4;
+    let area = utils::centered_rect(
+        Constraint::Percentage(60),
+        Constraint::Length(total_height.min(f.area().height)),
+        f.area(),
+    );
+    let visible = area.height.saturating_sub(4);
+    app.help_scroll = app
+        .help_scroll
+        .min((rows.len() as u16).saturating_sub(visible));
+
+    let mut instructions = vec![
         " To close, press ".into(),
         "h".blue().bold(),
-        " again ".into(),
-    ]);
+        " again".into(),
+    ];
+    if visible < rows.len() as u16 {
+        instructions.extend([
+            ", to scroll ".into(),
+            "j".blue().bold(),
+            " and ".into(),
+            "k".blue().bold(),
+        ]);
+    }
+    instructions.push(" ".into());
     let block = Block::default()
         .title(" Help ")
-        .title_bottom(close_help.centered())
+        .title_bottom(Line::from(instructions).centered())
         .borders(Borders::ALL);
 
-    let rows = [
-        Row::new(vec!["t", "Today's tasks"]),
-        Row::new(vec!["q", "Quit"]),
-        Row::new(vec![
-            "x",
-            "Press x while a task is highlighted to mark as done",
-        ]),
-        Row::new(vec![
-            "d",
-            "Press d while a task is highlighted to delete the task",
-        ]),
-        Row::new(vec![
-            "+",
-            "Press + while on a project to add task to the project",
-        ]),
-        Row::new(vec!["o", "Overdue tasks"]),
-        Row::new(vec!["a", "All tasks"]),
-        Row::new(vec!["p", "Sort by priority"]),
-        Row::new(vec!["d", "Sort by date"]),
-        Row::new(vec!["Tab", "Switch between projects and tasks"]),
-    ];
-    let row_count = rows.len();
-    let total_height = row_count + 5;
-
-    let widths = [Constraint::Percentage(50), Constraint::Percentage(50)];
+    let widths = [Constraint::Length(12), Constraint::Fill(1)];
     let table = Table::new(rows, widths)
         .header(
             Row::new(vec!["Shortcut", "Functionality"])
@@ -49,11 +137,7 @@ pub fn help(f: &mut Frame) {
         )
         .block(block);
 
-    let area = utils::centered_rect(
-        Constraint::Percentage(60),
-        Constraint::Length(total_height as u16),
-        f.area(),
-    );
+    let mut state = TableState::default().with_offset(app.help_scroll as usize);
     f.render_widget(Clear, area);
-    f.render_widget(table, area);
+    f.render_stateful_widget(table, area, &mut state);
 }
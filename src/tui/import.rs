@@ -0,0 +1,73 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::{tui::utils, App};
+
+/// The tasks an import would add, under their sections, in a modal.
+pub fn preview(f: &mut Frame, app: &App) {
+    let Some(preview) = &app.import else {
+        return;
+    };
+    let area = utils::centered_rect(
+        Constraint::Percentage(60),
+        Constraint::Percentage(60),
+        f.area(),
+    );
+    f.render_widget(Clear, area);
+
+    let project_name = app
+        .projects
+        .projects
+        .iter()
+        .find(|project| project.id == preview.project_id)
+        .map_or("", |project| project.name.as_str());
+    let section_exists = |name: &str| {
+        app.sections.sections.iter().any(|section| {
+            section.project_id == preview.project_id && section.name.eq_ignore_ascii_case(name)
+        })
+    };
+
+    let mut lines = vec![
+        Line::from(format!("From {}", preview.path.display())).fg(Color::DarkGray),
+        Line::from(""),
+    ];
+    for (index, draft) in preview.import.drafts.iter().enumerate() {
+        if preview.import.starts_section(index) {
+            let name = draft.section.as_deref().unwrap_or("No section");
+            let mut heading = vec![Span::styled(name.to_string(), Style::default().bold())];
+            if draft.section.is_some() && !section_exists(name) {
+                heading.push(Span::styled(
+                    " (new section)",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(heading));
+        }
+        let indent = "  ".repeat(preview.import.depth(index));
+        lines.push(Line::from(format!("{}[ ] {}", indent, draft.summary())));
+    }
+
+    let instructions = Line::from(vec![
+        " Press ".into(),
+        "Enter".blue().bold(),
+        " to add them, ".into(),
+        "Tab".blue().bold(),
+        " for another project and ".into(),
+        "Esc".blue().bold(),
+        " to cancel ".into(),
+    ]);
+    let block = Block::default()
+        .title(format!(" Add {} ", preview.import.describe(project_name)))
+        .title_bottom(instructions.centered())
+        .borders(Borders::ALL)
+        .fg(Color::Indexed(47));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(Color::White))
+        .scroll((preview.scroll, 0));
+    f.render_widget(paragraph, area);
+}
@@ -0,0 +1,18 @@
+use chrono::Local;
+use ratatui::{prelude::*, widgets::Paragraph};
+
+use crate::tui::utils;
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+pub fn loading(f: &mut Frame, area: Rect) {
+    let frame = (Local::now().timestamp_millis() / 100) as usize % SPINNER.len();
+
+    let text = Line::from(vec![
+        Span::styled(SPINNER[frame], Style::default().fg(Color::Indexed(47))),
+        " Fetching your projects, tasks and sections".into(),
+    ]);
+
+    let area = utils::centered_rect(Constraint::Percentage(80), Constraint::Length(1), area);
+    f.render_widget(Paragraph::new(text).centered(), area);
+}
@@ -1,9 +1,9 @@
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Clear},
+    widgets::{Block, Borders, Clear, Paragraph},
 };
 
-use crate::{new_task::CurrentlyEditing, tui::utils, App};
+use crate::{new_task::CurrentlyEditing, tasks, tui::utils, App};
 
 pub fn editor(f: &mut Frame, app: &mut App) {
     let area = utils::centered_rect(
@@ -14,6 +14,10 @@ pub fn editor(f: &mut Frame, app: &mut App) {
 
     f.render_widget(Clear, area);
 
+    if app.new_task.quick_add.is_some() {
+        return quick_add(f, app, area);
+    }
+
     let inner_area = area.inner(Margin {
         vertical: 1,
         horizontal: 1,
@@ -25,6 +29,13 @@ pub fn editor(f: &mut Frame, app: &mut App) {
             Constraint::Length(3),
             Constraint::Length(5),
             Constraint::Min(1),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(if app.new_task.sections.is_empty() {
+                0
+            } else {
+                1
+            }),
         ])
         .split(inner_area);
 
@@ -57,13 +68,52 @@ pub fn editor(f: &mut Frame, app: &mut App) {
             }),
     );
 
+    app.new_task.labels.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Labels, comma separated")
+            .fg(match app.new_task.currently_editing {
+                CurrentlyEditing::Labels => Color::Indexed(47),
+                _ => Color::White,
+            }),
+    );
+
     let content = &app.new_task.content;
     let description = &app.new_task.description;
     let due_string = &app.new_task.due_string;
+    let labels = &app.new_task.labels;
 
     f.render_widget(content, vertical_split[0]);
     f.render_widget(description, vertical_split[1]);
     f.render_widget(due_string, vertical_split[2]);
+    f.render_widget(labels, vertical_split[3]);
+
+    let priority = utils::selector(
+        "Priority",
+        tasks::priority_name(app.new_task.priority()),
+        app.new_task.currently_editing == CurrentlyEditing::Priority,
+    );
+    f.render_widget(Paragraph::new(priority), vertical_split[4]);
+
+    if !app.new_task.sections.is_empty() {
+        let section = app
+            .new_task
+            .section_id
+            .as_ref()
+            .and_then(|id| {
+                app.new_task
+                    .sections
+                    .iter()
+                    .find(|section| section.id == *id)
+            })
+            .map_or("None", |section| section.name.as_str());
+        let line = utils::selector(
+            "Section",
+            section,
+            app.new_task.currently_editing == CurrentlyEditing::Section,
+        );
+        f.render_widget(Paragraph::new(line), vertical_split[5]);
+    }
 
     let close_modal_desc = Line::from(vec![
         " To save, press ".into(),
@@ -79,3 +129,135 @@ pub fn editor(f: &mut Frame, app: &mut App) {
 
     f.render_widget(block, area);
 }
+
+/// The quick add line and, under it, what it will create.
+fn quick_add(f: &mut Frame, app: &mut App, area: Rect) {
+    let inner_area = area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let vertical_split = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .split(inner_area);
+
+    let Some((parsed, resolved)) = app.new_task.parse_quick_add(
+        &app.projects.projects,
+        &app.sections.sections,
+        &app.collaborators,
+    ) else {
+        return;
+    };
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let field = |name: &str, value: Option<String>, understood: bool| {
+        let value = match value {
+            Some(value) if understood => Span::raw(value),
+            Some(value) => Span::styled(value, Style::default().fg(Color::Red)),
+            None => Span::styled("-", dim),
+        };
+        Line::from(vec![Span::styled(format!(" {:<10}", name), dim), value])
+    };
+    let project = resolved
+        .project_id
+        .as_ref()
+        .and_then(|id| app.projects.projects.iter().find(|p| p.id == *id))
+        .map(|project| project.name.clone());
+    let section = resolved
+        .section_id
+        .as_ref()
+        .and_then(|id| app.sections.sections.iter().find(|s| s.id == *id))
+        .map(|section| section.name.clone());
+    let assignee = resolved
+        .assignee
+        .as_ref()
+        .map(|assignee| assignee.name.clone());
+    let deadline = resolved
+        .deadline
+        .map(|deadline| deadline.format("%a %-d %b %Y").to_string());
+    let labels = Some(parsed.labels.join(", ")).filter(|labels| !labels.is_empty());
+
+    let mut lines = vec![
+        field(
+            "Task",
+            Some(parsed.content.clone()).filter(|c| !c.is_empty()),
+            true,
+        ),
+        field(
+            "Project",
+            project.clone().or(parsed.project.clone()),
+            project.is_some(),
+        ),
+        field(
+            "Section",
+            section.clone().or(parsed.section.clone()),
+            section.is_some(),
+        ),
+        field("Labels", labels, true),
+        field(
+            "Priority",
+            Some(tasks::priority_name(parsed.priority.unwrap_or(1)).to_string()),
+            true,
+        ),
+        field(
+            "Assignee",
+            assignee.clone().or(parsed.assignee.clone()),
+            assignee.is_some(),
+        ),
+        field(
+            "Deadline",
+            deadline.clone().or(parsed.deadline.clone()),
+            deadline.is_some(),
+        ),
+        field("Due", parsed.due_string.clone(), true),
+        Line::default(),
+    ];
+    lines.extend(
+        resolved
+            .problems
+            .iter()
+            .map(|problem| Line::styled(format!(" {}", problem), Style::default().fg(Color::Red))),
+    );
+    lines.extend(
+        resolved
+            .hints
+            .iter()
+            .map(|hint| Line::styled(format!(" {}", hint), dim)),
+    );
+
+    let Some(line) = &mut app.new_task.quick_add else {
+        return;
+    };
+    line.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Task")
+            .fg(Color::Indexed(47)),
+    );
+    f.render_widget(&*line, vertical_split[0]);
+    f.render_widget(
+        Paragraph::new(" #project /section @label p1-p4 +assignee {deadline}, due date last")
+            .style(dim),
+        vertical_split[1],
+    );
+    f.render_widget(Paragraph::new(lines), vertical_split[2]);
+
+    let instructions = Line::from(vec![
+        " To add, press ".into(),
+        "Enter".blue().bold(),
+        ", for the full form ".into(),
+        "Tab".blue().bold(),
+        " and to close ".into(),
+        "Esc".blue().bold(),
+        " ".into(),
+    ]);
+    let block = Block::default()
+        .title(" Quick add ")
+        .title_bottom(instructions.centered())
+        .borders(Borders::ALL);
+    f.render_widget(block, area);
+}
@@ -0,0 +1,109 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::{
+    project_edit::{CurrentlyEditing, COLORS},
+    projects::ListType,
+    tui::utils,
+    App,
+};
+
+pub fn editor(f: &mut Frame, app: &mut App) {
+    let area = utils::centered_rect(Constraint::Percentage(60), Constraint::Length(11), f.area());
+
+    f.render_widget(Clear, area);
+
+    let inner_area = area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+
+    let vertical_split = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(3), Constraint::Min(1)])
+        .split(inner_area);
+
+    let project_edit = &mut app.project_edit;
+    project_edit
+        .name
+        .set_block(Block::default().borders(Borders::ALL).title("Name").fg(
+            match project_edit.currently_editing {
+                CurrentlyEditing::Name => Color::Indexed(47),
+                _ => Color::White,
+            },
+        ));
+    f.render_widget(&project_edit.name, vertical_split[0]);
+
+    let (color_name, color) = COLORS[project_edit.color];
+    let parent = project_edit
+        .parents
+        .get(project_edit.parent)
+        .map_or("None", |(_, name)| name.trim_start());
+    let view_style = match project_edit.view_style {
+        ListType::List => "List",
+        ListType::Board => "Board",
+    };
+    let favorite = if project_edit.is_favorite {
+        "Yes"
+    } else {
+        "No"
+    };
+
+    let fields = [
+        (
+            CurrentlyEditing::Color,
+            "Color",
+            Span::styled(color_name, Style::default().fg(color)),
+        ),
+        (CurrentlyEditing::Parent, "Parent", Span::raw(parent)),
+        (CurrentlyEditing::Favorite, "Favorite", Span::raw(favorite)),
+        (CurrentlyEditing::ViewStyle, "View", Span::raw(view_style)),
+    ];
+
+    let lines: Vec<Line> = fields
+        .into_iter()
+        .map(|(field, label, value)| {
+            let focused = project_edit.currently_editing == field;
+            let label_style = if focused {
+                Style::default().fg(Color::Indexed(47)).bold()
+            } else {
+                Style::default()
+            };
+            let (left, right) = if focused {
+                ("◂ ", " ▸")
+            } else {
+                ("  ", "  ")
+            };
+            Line::from(vec![
+                Span::styled(format!(" {:<10}", label), label_style),
+                left.into(),
+                value,
+                right.into(),
+            ])
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), vertical_split[1]);
+
+    let close_modal_desc = Line::from(vec![
+        " To save, press ".into(),
+        "Enter".blue().bold(),
+        " and to close, press ".into(),
+        "Esc".blue().bold(),
+    ]);
+
+    let title = if project_edit.project_id.is_some() {
+        " Edit project "
+    } else {
+        " New project "
+    };
+
+    let block = Block::default()
+        .title(title)
+        .title_bottom(close_modal_desc.centered())
+        .borders(Borders::ALL);
+
+    f.render_widget(block, area);
+}
@@ -0,0 +1,28 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear},
+};
+
+use crate::{prompt::Prompt, tui::utils};
+
+pub fn prompt(f: &mut Frame, prompt: &mut Prompt) {
+    let area = utils::centered_rect(Constraint::Percentage(50), Constraint::Length(3), f.area());
+
+    let instructions = Line::from(vec![
+        " To save, press ".into(),
+        "Enter".blue().bold(),
+        " and to close, press ".into(),
+        "Esc".blue().bold(),
+    ]);
+
+    prompt.input.set_block(
+        Block::default()
+            .title(format!(" {} ", prompt.title))
+            .title_bottom(instructions.centered())
+            .borders(Borders::ALL)
+            .fg(Color::Indexed(47)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(&prompt.input, area);
+}
@@ -1,14 +1,29 @@
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Clear, HighlightSpacing, List},
+    widgets::{Block, Borders, Clear, HighlightSpacing, List, Paragraph},
 };
 
-use crate::{task_edit::CurrentlyEditing, tui::utils, App};
+use crate::{
+    task_edit::CurrentlyEditing,
+    tasks,
+    tui::{comments, utils},
+    App,
+};
 
 pub fn editor(f: &mut Frame, app: &mut App) {
+    // completed or deleted elsewhere, and gone with the last refresh
+    if app.tasks.index_of(&app.task_edit.task_id).is_none() {
+        app.show_task_editor = false;
+        return;
+    }
+    let tasks = &app.tasks;
+    app.task_edit
+        .children
+        .retain(|id| tasks.index_of(id).is_some());
+
     let area = utils::centered_rect(
         Constraint::Percentage(60),
-        Constraint::Percentage(40),
+        Constraint::Percentage(60),
         f.area(),
     );
 
@@ -25,11 +40,19 @@ pub fn editor(f: &mut Frame, app: &mut App) {
             Constraint::Length(3),
             Constraint::Length(5),
             Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
             Constraint::Min(1),
         ])
         .split(inner_area);
 
-    let task_list_width = vertical_split[1].width as usize;
+    // sub tasks on the left, comments on the right
+    let bottom_split = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(vertical_split[5]);
+
+    let task_list_width = bottom_split[0].width as usize;
 
     let tasks_block = Block::default()
         .title(" Sub tasks ")
@@ -40,15 +63,22 @@ pub fn editor(f: &mut Frame, app: &mut App) {
         });
 
     let mut task_list_item = Vec::new();
-    for i in &app.task_edit.children {
-        let task = &app.tasks.tasks[*i];
-        let children: u16 = *app.tasks.tasks_with_children.get(&task.id).unwrap_or(&0);
+    for task in app
+        .task_edit
+        .children
+        .iter()
+        .filter_map(|id| app.tasks.index_of(id))
+        .map(|index| &app.tasks.tasks[index])
+    {
+        let marker = if app.tasks.tasks_with_children.contains_key(&task.id) {
+            "⤷"
+        } else {
+            " "
+        };
         task_list_item.push(utils::generate_list_item(
-            &task.content,
-            &task.due,
-            task.priority,
-            task.is_completed,
-            children,
+            task,
+            0,
+            marker,
             task_list_width - 4,
         ))
     }
@@ -92,13 +122,32 @@ pub fn editor(f: &mut Frame, app: &mut App) {
             },
         ));
 
+    app.task_edit.labels.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Labels, comma separated ")
+            .fg(match app.task_edit.currently_editing {
+                CurrentlyEditing::Labels => Color::Indexed(47),
+                _ => Color::White,
+            }),
+    );
+
     let content = &app.task_edit.content;
     let description = &app.task_edit.description;
     let due_string = &app.task_edit.due_string;
+    let labels = &app.task_edit.labels;
 
     f.render_widget(content, vertical_split[0]);
     f.render_widget(description, vertical_split[1]);
     f.render_widget(due_string, vertical_split[2]);
+    f.render_widget(labels, vertical_split[3]);
+
+    let priority = utils::selector(
+        "Priority",
+        tasks::priority_name(app.task_edit.priority),
+        app.task_edit.currently_editing == CurrentlyEditing::Priority,
+    );
+    f.render_widget(Paragraph::new(priority), vertical_split[4]);
 
     let close_modal_desc = Line::from(vec![
         " To save, press ".into(),
@@ -114,9 +163,12 @@ pub fn editor(f: &mut Frame, app: &mut App) {
 
     f.render_stateful_widget(
         task_list,
-        vertical_split[3],
+        bottom_split[0],
         &mut app.task_edit.children_list_state,
     );
 
+    let focused = app.task_edit.currently_editing == CurrentlyEditing::Comments;
+    comments::comment_list(f, app, bottom_split[1], focused);
+
     f.render_widget(block, area);
 }
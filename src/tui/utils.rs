@@ -2,7 +2,10 @@ use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
 use ratatui::{prelude::*, widgets::ListItem};
 use textwrap::fill;
 
-use crate::tasks::Due;
+use crate::tasks::{self, Task};
+
+/// How labels look next to a task.
+const LABEL_CHIP: Style = Style::new().fg(Color::Black).bg(Color::Indexed(140));
 
 pub fn centered_rect(horizontal: Constraint, vertical: Constraint, r: Rect) -> Rect {
     let popup_layout = match vertical {
@@ -44,23 +47,30 @@ pub fn centered_rect(horizontal: Constraint, vertical: Constraint, r: Rect) -> R
     }
 }
 
+/// A value picked with the arrow keys, e.g. ` Section ◂ Backlog ▸`.
+pub fn selector<'a>(name: &'a str, value: &'a str, focused: bool) -> Line<'a> {
+    let (name_style, left, right) = if focused {
+        (Style::default().fg(Color::Indexed(47)).bold(), "◂ ", " ▸")
+    } else {
+        (Style::default(), "  ", "  ")
+    };
+    Line::from(vec![
+        Span::styled(format!(" {} ", name), name_style),
+        left.into(),
+        value.into(),
+        right.into(),
+    ])
+}
+
 pub fn generate_list_item<'a>(
-    content: &String,
-    due: &Option<Due>,
-    priority: u8,
-    is_completed: bool,
-    children: u16,
+    task: &Task,
+    depth: usize,
+    marker: &str,
     width: usize,
 ) -> ListItem<'a> {
-    let color = match priority {
-        1 => "P4",
-        2 => "P3",
-        3 => "P2",
-        4 => "P1",
-        _ => "",
-    };
+    let color = tasks::priority_name(task.priority);
 
-    let formatted_due = if let Some(due) = due {
+    let formatted_due = if let Some(due) = &task.due {
         if let Some(datetime_str) = &due.datetime {
             if let Ok(datetime) = NaiveDateTime::parse_from_str(datetime_str, "%Y-%m-%dT%H:%M:%S") {
                 format!(
@@ -80,23 +90,37 @@ pub fn generate_list_item<'a>(
     };
 
     let formatted_text = format!(
-        "[{}] {} {} {} - {}",
-        if is_completed { "✓" } else { " " },
-        if children > 0 { "⤷" } else { " " },
-        content,
+        "{}[{}] {} {} {} - {}",
+        "  ".repeat(depth),
+        if task.is_completed { "✓" } else { " " },
+        marker,
+        task.content,
         formatted_due,
         color
     );
 
     let wrapped_text = fill(&formatted_text, width);
+    let mut lines: Vec<Line> = wrapped_text
+        .lines()
+        .map(|line| Line::from(line.to_string()))
+        .collect();
+
+    // labels follow the text and wrap like it does
+    for label in &task.labels {
+        let chip = Span::styled(format!(" @{} ", label), LABEL_CHIP);
+        match lines.last_mut() {
+            Some(line) if line.width() + chip.width() < width => {
+                line.push_span(" ");
+                line.push_span(chip);
+            }
+            _ => lines.push(Line::from(chip)),
+        }
+    }
 
-    ListItem::new(Text::styled(
-        wrapped_text,
-        Style::default().fg(Color::White),
-    ))
+    ListItem::new(Text::from(lines).style(Style::default().fg(Color::White)))
 }
 
-fn format_date(date: NaiveDate) -> String {
+pub fn format_date(date: NaiveDate) -> String {
     let month = match date.month() {
         1 => "Jan",
         2 => "Feb",